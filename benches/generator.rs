@@ -0,0 +1,52 @@
+//! Run with `cargo bench`
+//!
+//! Plain `Instant` harness alike `benches/id.rs`, crate stays dependency free
+extern crate flowerid;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use flowerid::config::SEQUENCE_MAX;
+use flowerid::generator::FIDGenerator;
+
+const ITERATIONS: u32 = 1_000_000;
+const BATCH: usize = 1024;
+
+fn bench<F: FnMut()>(name: &str, iterations: u32, mut f: F) {
+    for _ in 0..iterations / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.2} ns/iter",
+        name,
+        elapsed.as_secs_f64() * 1e9 / iterations as f64
+    );
+}
+
+fn main() {
+    // sequence is rewound before overflow, so no iteration waits next timestamp
+    let mut gen = FIDGenerator::with_id(0x12c).unwrap();
+    bench("FIDGenerator::next", ITERATIONS, || {
+        if gen.current_sequence() == SEQUENCE_MAX {
+            let timestamp = gen.timestamp_last();
+            gen.reset_to(timestamp, 0).unwrap();
+        }
+        black_box(gen.next().unwrap());
+    });
+
+    // includes waiting next timestamp on sequence overflow
+    let mut gen = FIDGenerator::with_id(0x12c).unwrap();
+    let mut batch = Vec::with_capacity(BATCH);
+    bench("FIDGenerator::next x1024", ITERATIONS / BATCH as u32, || {
+        batch.clear();
+        for _ in 0..BATCH {
+            batch.push(gen.next().unwrap());
+        }
+        black_box(&batch);
+    });
+}
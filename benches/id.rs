@@ -0,0 +1,49 @@
+//! Run with `cargo bench`
+extern crate flowerid;
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use flowerid::base64;
+use flowerid::FID;
+
+const ITERATIONS: u32 = 1_000_000;
+
+fn bench<F: FnMut()>(name: &str, mut f: F) {
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.2} ns/iter",
+        name,
+        elapsed.as_secs_f64() * 1e9 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    let b64 = fid.to_b64();
+    let bytes = fid.to_bytes();
+
+    bench("FID::from_b64", || {
+        black_box(FID::from_b64(black_box(&b64)).unwrap());
+    });
+    bench("FID::from_b64_exact", || {
+        black_box(FID::from_b64_exact(black_box(&b64)).unwrap());
+    });
+    bench("FID::to_b64", || {
+        black_box(black_box(&fid).to_b64());
+    });
+    bench("base64::encode", || {
+        black_box(base64::urlsafe_encode_without_pading(black_box(&bytes)));
+    });
+    bench("base64::decode", || {
+        let ignore = Some(flowerid::Error::Base64PaddingError);
+        black_box(base64::decode(black_box(&b64), ignore).unwrap());
+    });
+}
@@ -0,0 +1,53 @@
+//! Run with `cargo bench`
+//!
+//! Generation is bounded by 2048 ids per millisecond, so results show
+//! contention overhead on top of sequence waiting.
+extern crate flowerid;
+
+use std::hint::black_box;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use flowerid::generator::{FIDGenerator, FIDGeneratorBuilder};
+use flowerid::shared::{AtomicGenerator, SharedGenerator};
+use flowerid::FID;
+
+const THREADS: u32 = 4;
+const ITERATIONS: u32 = 250_000;
+
+fn bench<F: Fn() -> FID + Send + Sync + 'static>(name: &str, f: F) {
+    let f = Arc::new(f);
+    let start = Instant::now();
+    let threads: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let f = f.clone();
+            thread::spawn(move || {
+                for _ in 0..ITERATIONS {
+                    black_box(f());
+                }
+            })
+        })
+        .collect();
+    for x in threads {
+        x.join().unwrap();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<24} {:>8.2} ns/id ({} threads)",
+        name,
+        elapsed.as_secs_f64() * 1e9 / (THREADS * ITERATIONS) as f64,
+        THREADS
+    );
+}
+
+fn main() {
+    let gen = Mutex::new(FIDGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap());
+    bench("Mutex<FIDGenerator>", move || gen.lock().unwrap().next().unwrap());
+
+    let gen = SharedGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
+    bench("SharedGenerator", move || gen.next().unwrap());
+
+    let gen = AtomicGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
+    bench("AtomicGenerator", move || gen.next().unwrap());
+}
@@ -13,6 +13,12 @@ extern "C" {
 #define FID_RESULT_WRONGSLICESIZE -6
 #define FID_RESULT_BASE64DECODEERROR -7
 #define FID_RESULT_BUFFERWRONGSIZE -8
+#define FID_RESULT_INVALIDOFFSET -9
+#define FID_RESULT_RATELIMITED -10
+#define FID_RESULT_SYSTIMETOOFARAHEAD -11
+
+#define FID_STR_LEN 11
+#define FID_BYTE_LEN 8
 
 typedef uint64_t FID;
 typedef void *FID_GENERATOR;
@@ -23,12 +29,19 @@ int32_t flowerid_from_bytes(FID *self, const uint8_t *buffer, size_t buffer_size
 int32_t flowerid_to_string(FID self, char *buffer, size_t buffer_size);
 int32_t flowerid_from_string(FID *self, const char *buffer);
 uint64_t flowerid_get_timestamp(FID self);
+int32_t flowerid_get_unix_millis(FID self, int64_t offset_secs, int32_t in_seconds, uint64_t *out);
 uint64_t flowerid_get_sequence(FID self);
 uint64_t flowerid_get_generator(FID self);
+int32_t flowerid_layout(uint32_t *timestamp_bits, uint32_t *sequence_bits, uint32_t *generator_bits);
+int32_t flowerid_shifts(uint32_t *timestamp_shift, uint32_t *sequence_shift, uint32_t *generator_shift);
+int32_t flowerid_sizes(size_t *byte_len, size_t *str_len);
 
 int32_t flowerid_generator_new(FID_GENERATOR *self, uint64_t generator, int32_t wait_sequence);
 int32_t flowerid_generator_new_ex(FID_GENERATOR *self, uint64_t generator, int64_t timestamp_offset, uint64_t timestamp_last, uint64_t sequence, int32_t wait_sequence, int32_t timestamp_in_seconds);
 int32_t flowerid_generator_next(FID_GENERATOR self, FID *dst);
+int32_t flowerid_generator_next_batch(FID_GENERATOR self, FID *dst, size_t count);
+int32_t flowerid_generator_peek(FID_GENERATOR self, FID *dst);
+int32_t flowerid_generator_remaining_sequence(FID_GENERATOR self, uint32_t *out);
 int32_t flowerid_generator_release(FID_GENERATOR self);
 
 #ifdef __cplusplus
@@ -9,6 +9,7 @@ use flowerid::id;
 use flowerid::config;
 use flowerid::generator;
 use flowerid::Error;
+use flowerid::{FID_BYTE_LEN, FID_STR_LEN};
 
 #[allow(non_camel_case_types)]
 pub type FID_C = uint64_t;
@@ -26,6 +27,9 @@ enum RESULT {
     WrongSliceSize = -6,
     Base64DecodeError = -7,
     BufferWrongSize = -8,
+    InvalidOffset = -9,
+    RateLimited = -10,
+    SysTimeTooFarAhead = -11,
 }
 
 impl From<RESULT> for i32 {
@@ -52,6 +56,20 @@ fn from_errori32(res: Error) -> i32 {
         Base64BufferTooSmall => From::from(RESULT::Base64DecodeError),
         Base64WrongSymbolError => From::from(RESULT::Base64DecodeError),
         Base64PaddingWrongSymbolError => From::from(RESULT::Base64DecodeError),
+        IntegerParseError => From::from(RESULT::InvalidArgument),
+        InvalidOffset(_, _) => From::from(RESULT::InvalidOffset),
+        RateLimited => From::from(RESULT::RateLimited),
+        InvalidRateLimit => From::from(RESULT::InvalidArgument),
+        InvalidEnvVar(_) => From::from(RESULT::InvalidArgument),
+        InvalidUuid => From::from(RESULT::InvalidArgument),
+        IoError => From::from(RESULT::InvalidArgument),
+        GeneratorInUse(_) => From::from(RESULT::InvalidArgument),
+        InvalidVersion(_) => From::from(RESULT::InvalidArgument),
+        ReservedBitConflict => From::from(RESULT::InvalidArgument),
+        SysTimeTooFarAhead => From::from(RESULT::SysTimeTooFarAhead),
+        Base64NonCanonical => From::from(RESULT::Base64DecodeError),
+        Sequence128Overflow(_) => From::from(RESULT::SequenceOverflow),
+        Generator128Overflow(_) => From::from(RESULT::GeneratorOverflow),
     }
 }
 
@@ -91,7 +109,7 @@ pub extern "C" fn flowerid_to_bytes(
     if buffer == ptr::null_mut() {
         return From::from(RESULT::InvalidArgument);
     }
-    if buffer_size != 8 {
+    if buffer_size != FID_BYTE_LEN {
         return From::from(RESULT::BufferWrongSize);
     }
     let result = id::FID::from(this).to_bytes();
@@ -112,11 +130,11 @@ pub extern "C" fn flowerid_from_bytes(
     if dst == ptr::null_mut() || buffer == ptr::null() {
         return From::from(RESULT::InvalidArgument);
     }
-    if buffer_size != 8 {
+    if buffer_size != FID_BYTE_LEN {
         return From::from(RESULT::BufferWrongSize);
     }
     unsafe {
-        let mut tmp: [u8; 8] = mem::uninitialized();
+        let mut tmp: [u8; FID_BYTE_LEN] = mem::uninitialized();
         for (i, x) in tmp.iter_mut().enumerate() {
             *x = *buffer.offset(i as isize);
         }
@@ -135,7 +153,7 @@ pub extern "C" fn flowerid_to_string(
     if buffer == ptr::null_mut() {
         return From::from(RESULT::InvalidArgument);
     }
-    if buffer_size != 12 {
+    if buffer_size != FID_STR_LEN + 1 {
         return From::from(RESULT::BufferWrongSize);
     }
     let result = id::FID::from(this).to_b64();
@@ -143,9 +161,9 @@ pub extern "C" fn flowerid_to_string(
         for (i, x) in result.iter().enumerate() {
             *buffer.offset(i as isize) = *x as c_char;
         }
-        *buffer.offset(11) = 0;
+        *buffer.offset(FID_STR_LEN as isize) = 0;
     }
-    11
+    FID_STR_LEN as int32_t
 }
 
 #[no_mangle]
@@ -153,10 +171,10 @@ pub extern "C" fn flowerid_from_string(dst: *mut FID_C, buffer: *const c_char) -
     if dst == ptr::null_mut() || buffer == ptr::null() {
         return From::from(RESULT::InvalidArgument);
     }
-    if unsafe { strlen(buffer) } != 11 {
+    if unsafe { strlen(buffer) } != FID_STR_LEN {
         return From::from(RESULT::BufferWrongSize);
     }
-    let buffer = unsafe { slice::from_raw_parts(buffer as *const u8, 11) };
+    let buffer = unsafe { slice::from_raw_parts(buffer as *const u8, FID_STR_LEN) };
     match id::FID::from_b64(buffer) {
         Ok(id) => unsafe {
             flowerid_rust_to_c(dst, &id);
@@ -172,6 +190,31 @@ pub extern "C" fn flowerid_get_timestamp(dst: FID_C) -> uint64_t {
     id.timestamp()
 }
 
+#[no_mangle]
+pub extern "C" fn flowerid_get_unix_millis(
+    this: FID_C,
+    offset_secs: int64_t,
+    in_seconds: int32_t,
+    out: *mut uint64_t,
+) -> int32_t {
+    if out == ptr::null_mut() {
+        return From::from(RESULT::InvalidArgument);
+    }
+    let id = flowerid_c_to_rust(this);
+    let mut millis = id.timestamp() as i128;
+    if in_seconds != 0 {
+        millis *= 1000;
+    }
+    millis -= offset_secs as i128 * 1000;
+    if millis < 0 || millis > u64::max_value() as i128 {
+        return From::from(RESULT::TimestampOverflow);
+    }
+    unsafe {
+        *out = millis as uint64_t;
+    }
+    From::from(RESULT::OK)
+}
+
 #[no_mangle]
 pub extern "C" fn flowerid_get_sequence(dst: FID_C) -> uint64_t {
     let id = flowerid_c_to_rust(dst);
@@ -184,6 +227,58 @@ pub extern "C" fn flowerid_get_generator(dst: FID_C) -> uint64_t {
     id.generator() as u64
 }
 
+#[no_mangle]
+pub extern "C" fn flowerid_layout(
+    timestamp_bits: *mut uint32_t,
+    sequence_bits: *mut uint32_t,
+    generator_bits: *mut uint32_t,
+) -> int32_t {
+    if timestamp_bits == ptr::null_mut()
+        || sequence_bits == ptr::null_mut()
+        || generator_bits == ptr::null_mut()
+    {
+        return From::from(RESULT::InvalidArgument);
+    }
+    unsafe {
+        *timestamp_bits = config::TIMESTAMP_LENGTH as uint32_t;
+        *sequence_bits = config::SEQUENCE_LENGTH as uint32_t;
+        *generator_bits = config::GENERATOR_LENGTH as uint32_t;
+    }
+    From::from(RESULT::OK)
+}
+
+#[no_mangle]
+pub extern "C" fn flowerid_shifts(
+    timestamp_shift: *mut uint32_t,
+    sequence_shift: *mut uint32_t,
+    generator_shift: *mut uint32_t,
+) -> int32_t {
+    if timestamp_shift == ptr::null_mut()
+        || sequence_shift == ptr::null_mut()
+        || generator_shift == ptr::null_mut()
+    {
+        return From::from(RESULT::InvalidArgument);
+    }
+    unsafe {
+        *timestamp_shift = config::TIMESTAMP_SHIFT as uint32_t;
+        *sequence_shift = config::SEQUENCE_SHIFT as uint32_t;
+        *generator_shift = config::GENERATOR_SHIFT as uint32_t;
+    }
+    From::from(RESULT::OK)
+}
+
+#[no_mangle]
+pub extern "C" fn flowerid_sizes(byte_len: *mut size_t, str_len: *mut size_t) -> int32_t {
+    if byte_len == ptr::null_mut() || str_len == ptr::null_mut() {
+        return From::from(RESULT::InvalidArgument);
+    }
+    unsafe {
+        *byte_len = FID_BYTE_LEN as size_t;
+        *str_len = FID_STR_LEN as size_t;
+    }
+    From::from(RESULT::OK)
+}
+
 #[no_mangle]
 pub extern "C" fn flowerid_generator_new(
     dst: *mut FIDGEN_C,
@@ -253,6 +348,58 @@ pub extern "C" fn flowerid_generator_next(this: FIDGEN_C, dst: *mut FID_C) -> in
     }
 }
 
+#[no_mangle]
+pub extern "C" fn flowerid_generator_peek(this: FIDGEN_C, dst: *mut FID_C) -> int32_t {
+    if this == ptr::null_mut() || dst == ptr::null_mut() {
+        return From::from(RESULT::InvalidArgument);
+    }
+    unsafe {
+        match (*this).peek_next() {
+            Ok(id) => {
+                flowerid_rust_to_c(dst, &id);
+                From::from(RESULT::OK)
+            }
+            Err(err) => from_errori32(err),
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn flowerid_generator_remaining_sequence(
+    this: FIDGEN_C,
+    out: *mut uint32_t,
+) -> int32_t {
+    if this == ptr::null_mut() || out == ptr::null_mut() {
+        return From::from(RESULT::InvalidArgument);
+    }
+    unsafe {
+        *out = (*this).remaining_sequence() as uint32_t;
+    }
+    From::from(RESULT::OK)
+}
+
+#[no_mangle]
+pub extern "C" fn flowerid_generator_next_batch(
+    this: FIDGEN_C,
+    dst: *mut FID_C,
+    count: size_t,
+) -> int32_t {
+    if this == ptr::null_mut() || dst == ptr::null_mut() || count > i32::max_value() as size_t {
+        return From::from(RESULT::InvalidArgument);
+    }
+    unsafe {
+        let dst = slice::from_raw_parts_mut(dst, count);
+        for (i, item) in dst.iter_mut().enumerate() {
+            match (*this).next() {
+                Ok(id) => flowerid_rust_to_c(item, &id),
+                Err(err) if i == 0 => return from_errori32(err),
+                Err(_) => return i as int32_t,
+            }
+        }
+    }
+    count as int32_t
+}
+
 #[no_mangle]
 pub extern "C" fn flowerid_generator_release(this: FIDGEN_C) -> int32_t {
     if this == ptr::null_mut() {
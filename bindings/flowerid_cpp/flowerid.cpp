@@ -37,6 +37,12 @@ const char *Error::what() const noexcept
         return "base64 decode error";
     case FID_RESULT_BUFFERWRONGSIZE:
         return "wrong buffer size error";
+    case FID_RESULT_INVALIDOFFSET:
+        return "invalid timestamp offset error";
+    case FID_RESULT_RATELIMITED:
+        return "rate limit error";
+    case FID_RESULT_SYSTIMETOOFARAHEAD:
+        return "system time too far ahead error";
     default:
         if (this->_code > 0)
             return "no error but failed";
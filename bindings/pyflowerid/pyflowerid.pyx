@@ -22,6 +22,9 @@ cdef extern from "flowerid.h":
     cdef int FID_RESULT_WRONGSLICESIZE
     cdef int FID_RESULT_BASE64DECODEERROR
     cdef int FID_RESULT_BUFFERWRONGSIZE
+    cdef int FID_RESULT_INVALIDOFFSET
+    cdef int FID_RESULT_RATELIMITED
+    cdef int FID_RESULT_SYSTIMETOOFARAHEAD
 
     int32_t flowerid_new(FID *self, uint64_t timestamp, uint64_t sequence, uint64_t generator)
     int32_t flowerid_to_bytes(FID self, uint8_t *buffer, size_t buffer_size)
@@ -64,6 +67,12 @@ class Error(Exception):
             return "base64 decode error"
         elif code == FID_RESULT_BUFFERWRONGSIZE:
             return "wrong buffer size error"
+        elif code == FID_RESULT_INVALIDOFFSET:
+            return "invalid timestamp offset error"
+        elif code == FID_RESULT_RATELIMITED:
+            return "rate limit error"
+        elif code == FID_RESULT_SYSTIMETOOFARAHEAD:
+            return "system time too far ahead error"
         return "unknown error"
 
     @property
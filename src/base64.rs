@@ -1,5 +1,9 @@
 //! Base64 encoder/decoder
 
+use core::result;
+use alloc::vec::Vec;
+use alloc::string::String;
+
 use {Error, Result};
 
 static ALPHABET: [u8; 64] = [
@@ -14,6 +18,12 @@ static ALPHABET_SAFE: [u8; 64] = [
     b'g', b'h', b'i', b'j', b'k', b'l', b'm', b'n', b'o', b'p', b'q', b'r', b's', b't', b'u', b'v',
     b'w', b'x', b'y', b'z', b'0', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'-', b'_',
 ];
+static ALPHABET_SORTABLE: [u8; 64] = [
+    b'-', b'0', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'A', b'B', b'C', b'D', b'E',
+    b'F', b'G', b'H', b'I', b'J', b'K', b'L', b'M', b'N', b'O', b'P', b'Q', b'R', b'S', b'T', b'U',
+    b'V', b'W', b'X', b'Y', b'Z', b'_', b'a', b'b', b'c', b'd', b'e', b'f', b'g', b'h', b'i', b'j',
+    b'k', b'l', b'm', b'n', b'o', b'p', b'q', b'r', b's', b't', b'u', b'v', b'w', b'x', b'y', b'z',
+];
 
 /// Encode bytes
 /// # Examples
@@ -30,6 +40,24 @@ pub fn encode_into(data: &[u8], buffer: &mut [u8]) -> Result<usize> {
     _encode_into(data, true, &ALPHABET, buffer)
 }
 
+/// Encode bytes appending result to `out`
+///
+/// Reuses `out` allocation, reserving capacity as needed
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::base64::encode_append;
+/// let mut out = Vec::new();
+/// encode_append(b"foo bar", &mut out);
+/// out.push(b'\n');
+/// encode_append(b"\xfb\xef\xff", &mut out);
+/// assert_eq!(out, b"Zm9vIGJhcg==\n++//");
+/// ```
+pub fn encode_append(data: &[u8], out: &mut Vec<u8>) {
+    _encode_append(data, true, &ALPHABET, out)
+}
+
 /// Encode bytes URL safe
 /// # Examples
 ///
@@ -75,6 +103,73 @@ pub fn urlsafe_encode_without_pading_into(data: &[u8], buffer: &mut [u8]) -> Res
     _encode_into(data, false, &ALPHABET_SAFE, buffer)
 }
 
+/// Encode bytes padded with `pad` symbol instead of `=`
+///
+/// # Failures
+/// `Error::Base64WrongSymbolError` if `pad` is base64 symbol
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::encode_with_padding;
+/// assert_eq!(encode_with_padding(b"foo bar", b'~').unwrap(), b"Zm9vIGJhcg~~");
+/// assert_eq!(encode_with_padding(b"foo", b'~').unwrap(), b"Zm9v");
+/// assert_eq!(encode_with_padding(b"foo bar", b'/').unwrap_err(), Error::Base64WrongSymbolError);
+/// ```
+pub fn encode_with_padding(data: &[u8], pad: u8) -> Result<Vec<u8>> {
+    if decode_char(pad).is_some() {
+        return Err(Error::Base64WrongSymbolError);
+    }
+    let mut result = encode(data);
+    for x in result.iter_mut().rev().take_while(|x| **x == b'=') {
+        *x = pad;
+    }
+    Ok(result)
+}
+
+/// Encode bytes into `String`
+/// # Examples
+///
+/// ```
+/// use flowerid::base64::*;
+/// assert_eq!(encode_str(b"foo bar"), "Zm9vIGJhcg==");
+/// assert_eq!(urlsafe_encode_str(b"\xfb\xef\xff"), "--__");
+/// assert_eq!(encode_without_pading_str(b"foo bar"), "Zm9vIGJhcg");
+/// assert_eq!(urlsafe_encode_without_pading_str(b"\xfb\xef"), "--8");
+/// ```
+pub fn encode_str(data: &[u8]) -> String {
+    _encode_str(data, true, &ALPHABET)
+}
+pub fn urlsafe_encode_str(data: &[u8]) -> String {
+    _encode_str(data, true, &ALPHABET_SAFE)
+}
+pub fn encode_without_pading_str(data: &[u8]) -> String {
+    _encode_str(data, false, &ALPHABET)
+}
+pub fn urlsafe_encode_without_pading_str(data: &[u8]) -> String {
+    _encode_str(data, false, &ALPHABET_SAFE)
+}
+
+/// Encode bytes with sortable alphabet without padding
+///
+/// Alphabet `-0-9A-Z_a-z` is in ASCII order, so encoded strings of equal length
+/// sort the same way as the input bytes
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::base64::sortable_encode_without_pading;
+/// assert_eq!(sortable_encode_without_pading(b"foo bar"), b"Oaxj758WRV");
+/// assert_eq!(sortable_encode_without_pading(b"\x00\x00\xff"), b"--2z");
+/// ```
+pub fn sortable_encode_without_pading(data: &[u8]) -> Vec<u8> {
+    _encode(data, false, &ALPHABET_SORTABLE)
+}
+pub fn sortable_encode_without_pading_into(data: &[u8], buffer: &mut [u8]) -> Result<usize> {
+    _encode_into(data, false, &ALPHABET_SORTABLE, buffer)
+}
+
 fn _encode_into(
     data: &[u8],
     padding: bool,
@@ -82,11 +177,7 @@ fn _encode_into(
     buffer: &mut [u8],
 ) -> Result<usize> {
     let result_total_len = 4 * (data.len() / 3 + 1);
-    let padding_len = if data.len() % 3 > 0 {
-        3 - data.len() % 3
-    } else {
-        0
-    };
+    let padding_len = (3 - data.len() % 3) % 3;
 
     let result_len = {
         let mut result_len = 0;
@@ -96,7 +187,7 @@ fn _encode_into(
             let mut group = 0u32;
             for i in 0..3 {
                 if let Some(x) = data_it.next() {
-                    group |= (*x as u32) << 16 - (8 * i);
+                    group |= (*x as u32) << (16 - (8 * i));
                 } else {
                     if i == 0 {
                         break 'encode_loop;
@@ -130,16 +221,27 @@ fn _encode_into(
 
         Ok(result_len)
     } else {
-        Ok(result_len - padding_len)
+        // exact-size buffer stops before padding symbol is written
+        Ok(core::cmp::min(result_len, (data.len() * 4).div_ceil(3)))
     }
 }
 
+fn _encode_append(data: &[u8], padding: bool, aplhabet: &[u8; 64], out: &mut Vec<u8>) {
+    let start = out.len();
+    out.resize(start + 4 * data.len().div_ceil(3), 0);
+    let real_size = _encode_into(data, padding, aplhabet, &mut out[start..]).unwrap();
+    out.truncate(start + real_size);
+}
+
 fn _encode(data: &[u8], padding: bool, aplhabet: &[u8; 64]) -> Vec<u8> {
-    let full_size = 4 * (data.len() / 3 + 1);
-    let mut result = vec![0u8; full_size];
-    let real_size = _encode_into(data, padding, aplhabet, &mut result).unwrap();
-    result.resize(real_size, 0);
-    return result;
+    let mut result = Vec::new();
+    _encode_append(data, padding, aplhabet, &mut result);
+    result
+}
+
+fn _encode_str(data: &[u8], padding: bool, aplhabet: &[u8; 64]) -> String {
+    // alphabets and padding symbol are ASCII
+    unsafe { String::from_utf8_unchecked(_encode(data, padding, aplhabet)) }
 }
 
 /// Decode bytes
@@ -163,19 +265,134 @@ fn _encode(data: &[u8], padding: bool, aplhabet: &[u8; 64]) -> Vec<u8> {
 /// assert_eq!(decode(b"Zm9vIGJhcg!", Some(Error::Base64PaddingWrongSymbolError)).unwrap(), b"foo bar");
 /// ```
 pub fn decode(data: &[u8], ignore_error: Option<Error>) -> Result<Vec<u8>> {
-    let full_size = (data.len() / 4 + 1) * 3;
-    let mut result = vec![0u8; full_size];
+    let mut result = vec![0u8; max_decoded_len(data, b'=')];
     let real_size = decode_into(data, ignore_error, &mut result)?;
-    result.resize(real_size, 0);
-    return Ok(result);
+    result.truncate(real_size);
+    Ok(result)
+}
+
+/// Decode bytes padded with `pad` symbol instead of `=`
+///
+/// # Failures
+/// `Error::Base64WrongSymbolError` if `pad` is base64 symbol
+/// alike `decode`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::decode_with_padding;
+/// assert_eq!(decode_with_padding(b"Zm9vIGJhcg..", b'.', None).unwrap(), b"foo bar");
+/// assert_eq!(decode_with_padding(b"Zm9vIGJhcg==", b'.', None).unwrap_err(), Error::Base64WrongSymbolError);
+/// assert_eq!(decode_with_padding(b"Zm9vIGJhcg", b'A', None).unwrap_err(), Error::Base64WrongSymbolError);
+/// ```
+pub fn decode_with_padding(data: &[u8], pad: u8, ignore_error: Option<Error>) -> Result<Vec<u8>> {
+    if decode_char(pad).is_some() {
+        return Err(Error::Base64WrongSymbolError);
+    }
+    let mut result = vec![0u8; max_decoded_len(data, pad)];
+    let (real_size, _) = _decode_into(data, ignore_error, decode_char, pad, false, &mut result)
+        .map_err(|x| x.0)?;
+    result.truncate(real_size);
+    Ok(result)
+}
+
+/// Decode bytes skipping ASCII whitespace (MIME style)
+///
+/// `' '`, `\t`, `\r` and `\n` are skipped, otherwise alike `decode(data, None)`
+///
+/// # Failures
+/// `Error::Base64PaddingError`
+/// `Error::Base64WrongSymbolError`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::decode_mime;
+/// assert_eq!(decode_mime(b"Zm9v\r\nIGJh cg==\n").unwrap(), b"foo bar");
+/// assert_eq!(decode_mime(b"Zm9v\r\nIGJh!cg==").unwrap_err(), Error::Base64WrongSymbolError);
+/// ```
+pub fn decode_mime(data: &[u8]) -> Result<Vec<u8>> {
+    let data: Vec<u8> = data
+        .iter()
+        .cloned()
+        .filter(|x| !matches!(x, b' ' | b'\t' | b'\r' | b'\n'))
+        .collect();
+    decode(&data, None)
+}
+
+/// Decode bytes limiting output size
+///
+/// Input that would decode to more than `max_out` bytes is rejected
+/// before any allocation
+///
+/// # Failures
+/// `Error::Base64BufferTooSmall` input is too long
+/// `Error::Base64PaddingError`
+/// `Error::Base64WrongSymbolError`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::decode_bounded;
+/// assert_eq!(decode_bounded(b"Zm9vIGJhcg==", 7).unwrap(), b"foo bar");
+/// assert_eq!(decode_bounded(b"Zm9vIGJhcg==", 6).unwrap_err(), Error::Base64BufferTooSmall);
+/// ```
+pub fn decode_bounded(data: &[u8], max_out: usize) -> Result<Vec<u8>> {
+    let full_size = max_decoded_len(data, b'=');
+    if full_size > max_out {
+        return Err(Error::Base64BufferTooSmall);
+    }
+    let mut result = vec![0u8; full_size];
+    let real_size = decode_into(data, None, &mut result)?;
+    result.truncate(real_size);
+    Ok(result)
+}
+
+/// Decode bytes with strict padding validation
+///
+/// Input length must be a multiple of 4, padding (0, 1 or 2 `=`) may
+/// appear only at the end and no data may follow it (RFC 4648 §3.5)
+///
+/// # Failures
+/// `Error::Base64PaddingError`
+/// `Error::Base64WrongSymbolError`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::decode_strict;
+/// assert_eq!(decode_strict(b"Zm9vIGJhcg==").unwrap(), b"foo bar");
+/// assert_eq!(decode_strict(b"Zm9vIGJhcg").unwrap_err(), Error::Base64PaddingError);
+/// assert_eq!(decode_strict(b"Zm9v====").unwrap_err(), Error::Base64PaddingError);
+/// assert_eq!(decode_strict(b"Zm=vIGJh").unwrap_err(), Error::Base64PaddingError);
+/// ```
+pub fn decode_strict(data: &[u8]) -> Result<Vec<u8>> {
+    if !data.len().is_multiple_of(4) {
+        return Err(Error::Base64PaddingError);
+    }
+    let padding_len = data.iter().rev().take_while(|x| **x == b'=').count();
+    if padding_len > 2 || data[..data.len() - padding_len].contains(&b'=') {
+        return Err(Error::Base64PaddingError);
+    }
+    decode(data, None)
 }
 
-fn decode_char(x: u8) -> Option<u8> {
-    if b'A' <= x && x <= b'Z' {
+/// Upper bound of decoded length, trailing padding excluded
+fn max_decoded_len(data: &[u8], pad: u8) -> usize {
+    let padding_len = data.iter().rev().take_while(|x| **x == pad).count();
+    (data.len() - padding_len) * 3 / 4
+}
+
+pub(crate) fn decode_char(x: u8) -> Option<u8> {
+    if x.is_ascii_uppercase() {
         Some(x - b'A')
-    } else if b'a' <= x && x <= b'z' {
+    } else if x.is_ascii_lowercase() {
         Some(x - b'a' + 26)
-    } else if b'0' <= x && x <= b'9' {
+    } else if x.is_ascii_digit() {
         Some(x - b'0' + 26 * 2)
     } else if b'-' == x || b'+' == x {
         Some(0x3e)
@@ -186,7 +403,234 @@ fn decode_char(x: u8) -> Option<u8> {
     }
 }
 
+fn decode_char_sortable(x: u8) -> Option<u8> {
+    if x == b'-' {
+        Some(0)
+    } else if x.is_ascii_digit() {
+        Some(x - b'0' + 1)
+    } else if x.is_ascii_uppercase() {
+        Some(x - b'A' + 11)
+    } else if x == b'_' {
+        Some(37)
+    } else if x.is_ascii_lowercase() {
+        Some(x - b'a' + 38)
+    } else {
+        None
+    }
+}
+
 pub fn decode_into(data: &[u8], ignore_error: Option<Error>, buffer: &mut [u8]) -> Result<usize> {
+    _decode_into(data, ignore_error, decode_char, b'=', false, buffer)
+        .map(|x| x.0)
+        .map_err(|x| x.0)
+}
+
+/// Check data is canonical base64: decodes, and re-encoding decoded bytes
+/// reproduces data exactly
+///
+/// Padding and alphabet (standard or url-safe) of data are kept when re-encoding,
+/// so strings with non-zero discarded low bits, partial padding or mixed
+/// alphabets are rejected
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::base64::is_canonical;
+/// assert!(is_canonical(b"Zm9vIGJhcg=="));
+/// assert!(is_canonical(b"Zm9vIGJhcg"));
+/// assert!(!is_canonical(b"Zm9vIGJhch=="));
+/// assert!(!is_canonical(b"Zm9vIGJhcg="));
+/// assert!(!is_canonical(b"-_/+"));
+/// ```
+pub fn is_canonical(data: &[u8]) -> bool {
+    let decoded = match decode(data, Some(Error::Base64PaddingError)) {
+        Ok(x) => x,
+        Err(_) => return false,
+    };
+    let padded = data.last() == Some(&b'=');
+    let alphabet = if data.iter().any(|x| *x == b'+' || *x == b'/') {
+        &ALPHABET
+    } else {
+        &ALPHABET_SAFE
+    };
+    _encode(&decoded, padded, alphabet) == data
+}
+
+/// Decode bytes in place, decoded bytes are written into front of `buf`
+///
+/// Decoded output is shorter than input, so every group is read before its
+/// bytes are written. Alike `decode(buf, None)`, content of `buf` past
+/// returned length is unspecified
+///
+/// # Failures
+/// `Error::Base64PaddingError`
+/// `Error::Base64WrongSymbolError`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::base64::decode_in_place;
+/// let mut buf = *b"Zm9vIGJhcg==";
+/// let len = decode_in_place(&mut buf).unwrap();
+/// assert_eq!(&buf[..len], b"foo bar");
+/// ```
+pub fn decode_in_place(buf: &mut [u8]) -> Result<usize> {
+    let mut result_len = 0;
+    let mut pos = 0;
+    while pos < buf.len() {
+        let mut group = 0u32;
+        let mut group_len = 0usize;
+        let mut padded = false;
+        for i in 0..4 {
+            let x = match buf.get(pos + i) {
+                Some(x) => *x,
+                None => return Err(Error::Base64PaddingError),
+            };
+            if let Some(x) = decode_char(x) {
+                group |= (x as u32) << (18 - i * 6);
+                group_len += 6;
+            } else if x == b'=' {
+                if buf[pos + i..].iter().any(|x| *x != b'=') {
+                    return Err(Error::Base64PaddingError);
+                }
+                padded = true;
+                break;
+            } else {
+                return Err(Error::Base64WrongSymbolError);
+            }
+        }
+
+        for i in 0..3 {
+            if group_len >= 8 {
+                buf[result_len] = ((group >> (16 - 8 * i)) & 0xff) as u8;
+                result_len += 1;
+                group_len -= 8;
+            }
+        }
+        if padded {
+            break;
+        }
+        pos += 4;
+    }
+    Ok(result_len)
+}
+
+/// Decode bytes into fixed-size array without heap allocation
+///
+/// # Failures
+/// `Error::Base64BufferTooSmall` input decodes to more than `N` bytes
+/// `Error::WrongSliceSize` input decodes to less than `N` bytes (decoded length)
+/// `Error::Base64PaddingError`
+/// `Error::Base64WrongSymbolError`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::decode_exact;
+/// assert_eq!(&decode_exact::<7>(b"Zm9vIGJhcg==").unwrap(), b"foo bar");
+/// assert_eq!(decode_exact::<6>(b"Zm9vIGJhcg==").unwrap_err(), Error::Base64BufferTooSmall);
+/// assert_eq!(decode_exact::<8>(b"Zm9vIGJhcg==").unwrap_err(), Error::WrongSliceSize(7));
+/// ```
+pub fn decode_exact<const N: usize>(data: &[u8]) -> Result<[u8; N]> {
+    let mut result = [0u8; N];
+    let real_size = decode_into(data, None, &mut result)?;
+    if real_size == N {
+        Ok(result)
+    } else {
+        Err(Error::WrongSliceSize(real_size))
+    }
+}
+
+/// Decode bytes into buffer returning (output length, consumed input length)
+///
+/// alike `decode_into` but decoding stops after padding, so data following
+/// the padding (e.g. next concatenated field) is left unconsumed
+///
+/// # Failures
+/// `Error::Base64PaddingError`
+/// `Error::Base64WrongSymbolError`
+/// `Error::Base64BufferTooSmall`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::base64::decode_into_consumed;
+/// let data = b"Zm9vIGJhcg==YmF6";
+/// let mut buffer = [0u8; 16];
+/// let (len, consumed) = decode_into_consumed(data, None, &mut buffer).unwrap();
+/// assert_eq!(&buffer[..len], b"foo bar");
+/// assert_eq!(&data[consumed..], b"YmF6");
+/// ```
+pub fn decode_into_consumed(
+    data: &[u8],
+    ignore_error: Option<Error>,
+    buffer: &mut [u8],
+) -> Result<(usize, usize)> {
+    _decode_into(data, ignore_error, decode_char, b'=', true, buffer).map_err(|x| x.0)
+}
+
+/// Decode bytes encoded with sortable alphabet
+///
+/// see `sortable_encode_without_pading`
+///
+/// # Failures
+/// `Error::Base64PaddingError`
+/// `Error::Base64WrongSymbolError`
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::sortable_decode;
+/// assert_eq!(sortable_decode(b"Oaxj758WRV", Some(Error::Base64PaddingError)).unwrap(), b"foo bar");
+/// assert_eq!(sortable_decode(b"Oaxj758WR+", Some(Error::Base64PaddingError)).unwrap_err(), Error::Base64WrongSymbolError);
+/// ```
+pub fn sortable_decode(data: &[u8], ignore_error: Option<Error>) -> Result<Vec<u8>> {
+    let mut result = vec![0u8; max_decoded_len(data, b'=')];
+    let real_size = sortable_decode_into(data, ignore_error, &mut result)?;
+    result.truncate(real_size);
+    Ok(result)
+}
+pub fn sortable_decode_into(
+    data: &[u8],
+    ignore_error: Option<Error>,
+    buffer: &mut [u8],
+) -> Result<usize> {
+    _decode_into(data, ignore_error, decode_char_sortable, b'=', false, buffer)
+        .map(|x| x.0)
+        .map_err(|x| x.0)
+}
+
+/// Decode bytes reporting error position
+///
+/// alike `decode(data, None)` but on failure returns error with index of offending input symbol
+/// (`data.len()` if input ended unexpectedly)
+///
+/// # Examples
+///
+/// ```
+/// use flowerid::Error;
+/// use flowerid::base64::decode_pos;
+/// assert_eq!(decode_pos(b"Zm9vIGJhcg==").unwrap(), b"foo bar");
+/// assert_eq!(decode_pos(b"Zm9v!GJhcg==").unwrap_err(), (Error::Base64WrongSymbolError, 4));
+/// assert_eq!(decode_pos(b"Zm9vIGJhcg").unwrap_err(), (Error::Base64PaddingError, 10));
+/// ```
+pub fn decode_pos(data: &[u8]) -> result::Result<Vec<u8>, (Error, usize)> {
+    let mut result = vec![0u8; max_decoded_len(data, b'=')];
+    let (real_size, _) = _decode_into(data, None, decode_char, b'=', false, &mut result)?;
+    result.truncate(real_size);
+    Ok(result)
+}
+
+fn _decode_into(
+    data: &[u8],
+    ignore_error: Option<Error>,
+    decode_char: fn(u8) -> Option<u8>,
+    pad: u8,
+    stop_at_padding: bool,
+    buffer: &mut [u8],
+) -> result::Result<(usize, usize), (Error, usize)> {
     let (ignore_padding, ignore_symbol) = if let Some(x) = ignore_error {
         (
             x == Error::Base64PaddingError || x == Error::Base64PaddingWrongSymbolError,
@@ -198,28 +642,41 @@ pub fn decode_into(data: &[u8], ignore_error: Option<Error>, buffer: &mut [u8])
 
     let mut result_len = 0;
     let mut buffer_it = buffer.iter_mut();
-    let mut data_it = data.iter();
+    let mut data_it = data.iter().enumerate();
+    let mut padded = false;
     'decode_loop: loop {
         let mut group = 0u32;
         let mut group_len = 0usize;
         for i in 0..4 {
-            if let Some(x) = data_it.next() {
+            if let Some((pos, x)) = data_it.next() {
                 if let Some(x) = decode_char(*x) {
                     group |= (x as u32) << (18 - i * 6);
                     group_len += 6;
                 } else {
-                    if *x == b'=' {
-                        for x in data_it.clone() {
-                            if *x != b'=' {
-                                return Err(Error::Base64PaddingError);
+                    if *x == pad && stop_at_padding {
+                        while let Some((_, x)) = data_it.clone().next() {
+                            if *x != pad {
+                                break;
+                            }
+                            data_it.next();
+                        }
+                        padded = true;
+                        break;
+                    }
+                    if *x == pad {
+                        for (pos, x) in data_it.clone() {
+                            if *x != pad {
+                                return Err((Error::Base64PaddingError, pos));
                             }
                         }
+                        // rest is padding, skip it at once to keep decoding linear
+                        while data_it.next().is_some() {}
                         break;
                     }
                     if ignore_symbol {
                         break;
                     } else {
-                        return Err(Error::Base64WrongSymbolError);
+                        return Err((Error::Base64WrongSymbolError, pos));
                     }
                 }
             } else {
@@ -229,7 +686,7 @@ pub fn decode_into(data: &[u8], ignore_error: Option<Error>, buffer: &mut [u8])
                 if ignore_padding && i > 1 {
                     break;
                 } else {
-                    return Err(Error::Base64PaddingError);
+                    return Err((Error::Base64PaddingError, data.len()));
                 }
             }
         }
@@ -241,12 +698,15 @@ pub fn decode_into(data: &[u8], ignore_error: Option<Error>, buffer: &mut [u8])
                     result_len += 1;
                     group_len -= 8;
                 } else {
-                    return Err(Error::Base64BufferTooSmall);
+                    return Err((Error::Base64BufferTooSmall, data.len() - data_it.len()));
                 }
             }
         }
+        if padded {
+            break;
+        }
     }
-    Ok(result_len)
+    Ok((result_len, data.len() - data_it.len()))
 }
 
 #[cfg(test)]
@@ -288,6 +748,53 @@ mod test {
         assert_eq!(&super::urlsafe_encode(b"\xfb\xef\xff"), &b"--__");
     }
 
+    #[test]
+    fn encode_without_pading_into_exact() {
+        let cases = [&b"\x01"[..], b"\x01\x23", b"\x01\x23\x45", b">ok\x7f\xbfK\xe1\xcc"];
+        for data in cases.iter() {
+            let expected = super::encode_without_pading(data);
+            let mut exact = vec![0u8; expected.len()];
+            assert_eq!(super::encode_without_pading_into(data, &mut exact), Ok(expected.len()));
+            assert_eq!(exact, expected);
+            let mut large = vec![0u8; expected.len() + 4];
+            assert_eq!(super::encode_without_pading_into(data, &mut large), Ok(expected.len()));
+            assert_eq!(&large[..expected.len()], &expected[..]);
+        }
+    }
+
+    #[test]
+    fn encode_str() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        assert_eq!(super::encode_str(&data).as_bytes(), &super::encode(&data)[..]);
+        assert_eq!(
+            super::urlsafe_encode_str(&data).as_bytes(),
+            &super::urlsafe_encode(&data)[..]
+        );
+        assert_eq!(
+            super::encode_without_pading_str(&data).as_bytes(),
+            &super::encode_without_pading(&data)[..]
+        );
+        assert_eq!(
+            super::urlsafe_encode_without_pading_str(&data).as_bytes(),
+            &super::urlsafe_encode_without_pading(&data)[..]
+        );
+        assert_eq!(super::encode_str(b""), "");
+    }
+
+    #[test]
+    fn encode_append() {
+        let mut out = Vec::with_capacity(64);
+        let capacity = out.capacity();
+        for data in [&b""[..], b"\x01", b"\x01\x23", b"\x01\x23\x45"].iter() {
+            out.clear();
+            super::encode_append(data, &mut out);
+            assert_eq!(out, super::encode(data));
+        }
+        assert_eq!(out.capacity(), capacity);
+        super::encode_append(b"\x01", &mut out);
+        assert_eq!(&out, b"ASNFAQ==");
+    }
+
     #[test]
     fn decode() {
         let data_one_assert = b"\x01";
@@ -336,4 +843,348 @@ mod test {
         assert_eq!(&super::decode(b"++//", None).unwrap(), b"\xfb\xef\xff");
         assert_eq!(&super::decode(b"--__", None).unwrap(), b"\xfb\xef\xff");
     }
+
+    #[test]
+    fn decode_ignore_symbol() {
+        use super::Error as DE;
+        let ignore = Some(DE::Base64WrongSymbolError);
+        assert_eq!(&super::decode(b"Zm9vIGJh!", ignore).unwrap(), b"foo ba");
+        assert_eq!(&super::decode(b"Zm9vIGJhY!", ignore).unwrap(), b"foo ba");
+        assert_eq!(&super::decode(b"Zm9vIGJhY2!", ignore).unwrap(), b"foo bac");
+        assert_eq!(&super::decode(b"Zm9vIGJhY2F!", ignore).unwrap(), b"foo baca");
+        let data = b"Zm9vIGJhcmJh";
+        for i in 0..data.len() + 1 {
+            let mut input = data[..i].to_vec();
+            input.push(b'!');
+            assert_eq!(
+                &super::decode(&input, ignore).unwrap()[..],
+                &b"foo barba"[..i * 6 / 8]
+            );
+        }
+    }
+
+    #[test]
+    fn custom_padding() {
+        use super::Error as DE;
+        for data in [&b""[..], b"\x01", b"\x01\x23", b"\x01\x23\x45", b"foo bar"].iter() {
+            for pad in [b'.', b'~', b'='].iter() {
+                let encoded = super::encode_with_padding(data, *pad).unwrap();
+                let expected: Vec<u8> = super::encode(data)
+                    .iter()
+                    .map(|x| if *x == b'=' { *pad } else { *x })
+                    .collect();
+                assert_eq!(encoded, expected);
+                assert_eq!(&super::decode_with_padding(&encoded, *pad, None).unwrap(), data);
+            }
+        }
+        assert_eq!(
+            super::decode_with_padding(b"Zm9vIGJhcg.=", b'.', None).unwrap_err(),
+            DE::Base64PaddingError
+        );
+        assert_eq!(
+            super::decode_with_padding(b"Zm9vIGJhcg", b'.', Some(DE::Base64PaddingError)),
+            Ok(b"foo bar".to_vec())
+        );
+        for pad in [b'A', b'z', b'0', b'+', b'/', b'-', b'_'].iter() {
+            assert_eq!(
+                super::encode_with_padding(b"foo", *pad).unwrap_err(),
+                DE::Base64WrongSymbolError
+            );
+            assert_eq!(
+                super::decode_with_padding(b"Zm9v", *pad, None).unwrap_err(),
+                DE::Base64WrongSymbolError
+            );
+        }
+    }
+
+    /// xorshift64, deterministic input for property tests
+    fn random_bytes(state: &mut u64, max_len: usize, alphabet: &[u8]) -> Vec<u8> {
+        let mut next = || {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state
+        };
+        let len = next() as usize % (max_len + 1);
+        (0..len)
+            .map(|_| {
+                let x = next();
+                if alphabet.is_empty() {
+                    x as u8
+                } else {
+                    alphabet[x as usize % alphabet.len()]
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip_property() {
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..2000 {
+            let data = random_bytes(&mut state, 64, b"");
+            assert_eq!(super::decode(&super::encode(&data), None).unwrap(), data);
+            assert_eq!(super::decode(&super::urlsafe_encode(&data), None).unwrap(), data);
+            let unpadded = super::urlsafe_encode_without_pading(&data);
+            assert_eq!(
+                super::decode(&unpadded, Some(super::Error::Base64PaddingError)).unwrap(),
+                data
+            );
+            let sortable = super::sortable_encode_without_pading(&data);
+            assert_eq!(
+                super::sortable_decode(&sortable, Some(super::Error::Base64PaddingError)).unwrap(),
+                data
+            );
+            assert_eq!(super::decode_strict(&super::encode(&data)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn decode_never_panics() {
+        use super::Error as DE;
+        let ignore = [
+            None,
+            Some(DE::Base64PaddingError),
+            Some(DE::Base64WrongSymbolError),
+            Some(DE::Base64PaddingWrongSymbolError),
+        ];
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        for i in 0..4000 {
+            let data = if i % 2 == 0 {
+                random_bytes(&mut state, 40, b"")
+            } else {
+                random_bytes(&mut state, 40, b"AZaz09+/-_=!. ")
+            };
+            for x in ignore.iter() {
+                if let Ok(decoded) = super::decode(&data, *x) {
+                    assert!(decoded.len() <= data.len() * 3 / 4);
+                }
+                let _ = super::sortable_decode(&data, *x);
+                for len in 0..data.len() {
+                    let mut buffer = vec![0u8; len];
+                    if let Ok(n) = super::decode_into(&data, *x, &mut buffer) {
+                        assert!(n <= len);
+                    }
+                    if let Ok((n, consumed)) = super::decode_into_consumed(&data, *x, &mut buffer) {
+                        assert!(n <= len && consumed <= data.len());
+                    }
+                }
+            }
+            let _ = super::decode_pos(&data);
+            let _ = super::decode_strict(&data);
+            let _ = super::decode_mime(&data);
+            let _ = super::decode_bounded(&data, 8);
+            let _ = super::decode_exact::<8>(&data);
+            let _ = super::decode_with_padding(&data, b'.', None);
+        }
+        let huge = vec![b'A'; 1 << 20];
+        assert_eq!(super::decode(&huge, None).unwrap().len(), 3 << 18);
+        let mut huge = vec![b'='; 1 << 20];
+        huge[0] = b'A';
+        assert!(super::decode(&huge, Some(DE::Base64PaddingWrongSymbolError)).is_ok());
+    }
+
+    #[test]
+    fn is_canonical() {
+        let mut state = 0x0bad_cafe_dead_beef;
+        for _ in 0..1000 {
+            let data = random_bytes(&mut state, 40, b"");
+            assert!(super::is_canonical(&super::encode(&data)));
+            assert!(super::is_canonical(&super::urlsafe_encode_without_pading(&data)));
+        }
+        for x in [&b"Pm9rf79L4cx"[..], b"Pm9rf79L4c", b"Pm9r!79L4cw", b"AQ=", b"AR=="].iter() {
+            assert!(!super::is_canonical(x));
+        }
+        assert!(super::is_canonical(b"Pm9rf79L4cw"));
+        assert!(super::is_canonical(b"Pm9rf79L4cw="));
+        assert!(super::is_canonical(b"AQ=="));
+        assert!(super::is_canonical(b""));
+    }
+
+    #[test]
+    fn decode_in_place() {
+        use super::Error as DE;
+        let mut state = 0x1234_5678_9abc_def1;
+        for i in 0..4000 {
+            let data = if i % 2 == 0 {
+                super::encode(&random_bytes(&mut state, 40, b""))
+            } else {
+                random_bytes(&mut state, 40, b"AZaz09+/-_=!")
+            };
+            let mut buf = data.clone();
+            let expected = super::decode(&data, None);
+            match super::decode_in_place(&mut buf) {
+                Ok(len) => assert_eq!(Ok(buf[..len].to_vec()), expected),
+                Err(x) => assert_eq!(Err(x), expected),
+            }
+        }
+        let mut buf = *b"Zm9v!GJh";
+        assert_eq!(super::decode_in_place(&mut buf), Err(DE::Base64WrongSymbolError));
+        let mut buf = *b"Zm=vIGJh";
+        assert_eq!(super::decode_in_place(&mut buf), Err(DE::Base64PaddingError));
+        let mut buf = *b"Zm9vIGJhcg";
+        assert_eq!(super::decode_in_place(&mut buf), Err(DE::Base64PaddingError));
+        assert_eq!(super::decode_in_place(&mut []), Ok(0));
+    }
+
+    #[test]
+    fn decode_mime() {
+        use super::Error as DE;
+        assert_eq!(&super::decode_mime(b"").unwrap(), b"");
+        assert_eq!(&super::decode_mime(b" \t\r\n").unwrap(), b"");
+        assert_eq!(&super::decode_mime(b"A Q\t=\r=\n").unwrap(), b"\x01");
+        assert_eq!(&super::decode_mime(b"AS\r\nNF").unwrap(), b"\x01\x23\x45");
+        assert_eq!(super::decode_mime(b"AS\x0bNF").unwrap_err(), DE::Base64WrongSymbolError);
+        assert_eq!(super::decode_mime(b"AS N").unwrap_err(), DE::Base64PaddingError);
+        assert_eq!(super::decode(b"AS NF", None).unwrap_err(), DE::Base64WrongSymbolError);
+    }
+
+    #[test]
+    fn decode_bounded() {
+        use super::Error as DE;
+        assert_eq!(&super::decode_bounded(b"", 0).unwrap(), b"");
+        assert_eq!(&super::decode_bounded(b"AQ==", 1).unwrap(), b"\x01");
+        assert_eq!(&super::decode_bounded(b"ASM", 2).unwrap_err(), &DE::Base64PaddingError);
+        assert_eq!(&super::decode_bounded(b"ASNF", 3).unwrap(), b"\x01\x23\x45");
+        assert_eq!(
+            super::decode_bounded(b"ASNF", 2).unwrap_err(),
+            DE::Base64BufferTooSmall
+        );
+        assert_eq!(
+            super::decode_bounded(&vec![b'A'; 1 << 20], 8).unwrap_err(),
+            DE::Base64BufferTooSmall
+        );
+        assert_eq!(
+            super::decode_bounded(b"AS!F", 3).unwrap_err(),
+            DE::Base64WrongSymbolError
+        );
+    }
+
+    #[test]
+    fn sortable() {
+        use super::Error as DE;
+        for (i, x) in super::ALPHABET_SORTABLE.iter().enumerate() {
+            assert_eq!(super::decode_char_sortable(*x), Some(i as u8));
+            if i > 0 {
+                assert!(super::ALPHABET_SORTABLE[i - 1] < *x);
+            }
+        }
+        let mut data: Vec<Vec<u8>> = (0..512u32)
+            .map(|x| (x.wrapping_mul(0x9e37_79b9) >> 8).to_be_bytes().to_vec())
+            .collect();
+        let mut encoded: Vec<_> = data
+            .iter()
+            .map(|x| super::sortable_encode_without_pading(x))
+            .collect();
+        for (x, y) in data.iter().zip(encoded.iter()) {
+            assert_eq!(&super::sortable_decode(y, Some(DE::Base64PaddingError)).unwrap(), x);
+        }
+        data.sort();
+        encoded.sort();
+        for (x, y) in data.iter().zip(encoded.iter()) {
+            assert_eq!(&super::sortable_encode_without_pading(x), y);
+        }
+        assert_eq!(
+            super::sortable_decode(b"AS+F", None).unwrap_err(),
+            DE::Base64WrongSymbolError
+        );
+    }
+
+    #[test]
+    fn decode_pos() {
+        use super::Error as DE;
+        assert_eq!(&super::decode_pos(b"ASNF").unwrap(), b"\x01\x23\x45");
+        assert_eq!(
+            super::decode_pos(b"ASNF!").unwrap_err(),
+            (DE::Base64WrongSymbolError, 4)
+        );
+        assert_eq!(
+            super::decode_pos(b"AS=F").unwrap_err(),
+            (DE::Base64PaddingError, 3)
+        );
+        assert_eq!(
+            super::decode_pos(b"ASNFAS").unwrap_err(),
+            (DE::Base64PaddingError, 6)
+        );
+        let mut buffer = [0u8; 2];
+        assert_eq!(
+            super::_decode_into(b"ASNFASNF", None, super::decode_char, b'=', false, &mut buffer)
+                .unwrap_err(),
+            (DE::Base64BufferTooSmall, 4)
+        );
+    }
+
+    #[test]
+    fn decode_strict() {
+        use super::Error as DE;
+        assert_eq!(&super::decode_strict(b"").unwrap(), b"");
+        assert_eq!(&super::decode_strict(b"AQ==").unwrap(), b"\x01");
+        assert_eq!(&super::decode_strict(b"ASM=").unwrap(), b"\x01\x23");
+        assert_eq!(&super::decode_strict(b"ASNF").unwrap(), b"\x01\x23\x45");
+        for x in [&b"AQ"[..], b"ASM", b"A===", b"====", b"AQ==AQ==", b"A=Q=", b"ASNFAQ="].iter() {
+            assert_eq!(super::decode_strict(x).unwrap_err(), DE::Base64PaddingError);
+        }
+        assert_eq!(
+            super::decode_strict(b"AS!=").unwrap_err(),
+            DE::Base64WrongSymbolError
+        );
+    }
+
+    #[test]
+    fn decode_into_consumed() {
+        use super::Error as DE;
+        let mut buffer = [0u8; 8];
+        assert_eq!(super::decode_into_consumed(b"", None, &mut buffer).unwrap(), (0, 0));
+        assert_eq!(
+            super::decode_into_consumed(b"ASNF", None, &mut buffer).unwrap(),
+            (3, 4)
+        );
+        assert_eq!(
+            super::decode_into_consumed(b"AQ==ASM=", None, &mut buffer).unwrap(),
+            (1, 4)
+        );
+        assert_eq!(&buffer[..1], b"\x01");
+        assert_eq!(
+            super::decode_into_consumed(b"ASM=ASNF", None, &mut buffer).unwrap(),
+            (2, 4)
+        );
+        assert_eq!(
+            super::decode_into_consumed(b"ASNF==ASM", None, &mut buffer).unwrap(),
+            (3, 6)
+        );
+        assert_eq!(
+            super::decode_into_consumed(b"AQ", Some(DE::Base64PaddingError), &mut buffer)
+                .unwrap(),
+            (1, 2)
+        );
+        assert_eq!(
+            super::decode_into_consumed(b"AQ", None, &mut buffer).unwrap_err(),
+            DE::Base64PaddingError
+        );
+        assert_eq!(
+            super::decode_into_consumed(b"AS!=", None, &mut buffer).unwrap_err(),
+            DE::Base64WrongSymbolError
+        );
+    }
+
+    #[test]
+    fn decode_exact() {
+        use super::Error as DE;
+        assert_eq!(super::decode_exact::<0>(b"").unwrap(), [0u8; 0]);
+        assert_eq!(&super::decode_exact::<1>(b"AQ==").unwrap(), b"\x01");
+        assert_eq!(&super::decode_exact::<3>(b"ASNF").unwrap(), b"\x01\x23\x45");
+        assert_eq!(
+            super::decode_exact::<2>(b"ASNF").unwrap_err(),
+            DE::Base64BufferTooSmall
+        );
+        assert_eq!(
+            super::decode_exact::<4>(b"ASNF").unwrap_err(),
+            DE::WrongSliceSize(3)
+        );
+        assert_eq!(
+            super::decode_exact::<3>(b"AS!F").unwrap_err(),
+            DE::Base64WrongSymbolError
+        );
+        assert_eq!(super::decode_exact::<1>(b"AQ").unwrap_err(), DE::Base64PaddingError);
+    }
 }
@@ -21,8 +21,75 @@ pub mod gbuilder_defaults {
     pub const TIMESTAMP_IN_SECONDS: bool = false;
     /// Wait next timestamp if sequence is overflowed
     pub const WAIT_SEQUENCE: bool = true;
+    /// Keep last timestamp if system time went backward
+    pub const MONOTONIC: bool = false;
+    /// Busy-spin count before sleeping while waiting next timestamp
+    pub const WAIT_SPIN: u32 = 0;
+    /// Assumed system clock resolution (in milliseconds)
+    pub const CLOCK_RESOLUTION_MS: u64 = 1;
+    /// Set parity of lower bits into reserved high bit
+    pub const PARITY_BIT: bool = false;
+    /// Layout version stamped into reserved high bit
+    pub const VERSION: u8 = 0;
 }
 
+// Layout must fit u64, generator and sequence must fit u16
+const _: () = assert!(GENERATOR_LENGTH as u64 + SEQUENCE_LENGTH as u64 + TIMESTAMP_LENGTH <= 64);
+const _: () = assert!(GENERATOR_LENGTH <= 16 && SEQUENCE_LENGTH <= 16);
+
+/// Value with `length` low bits set, doesn't overflow for 0 and 64
+const fn low_bits(length: u64) -> u64 {
+    if length == 0 {
+        0
+    } else {
+        u64::MAX >> (64 - length)
+    }
+}
+
+// Don't change next constants
+/// Generator bit position (lowest field)
+pub const GENERATOR_SHIFT: u16 = 0;
+/// Sequence bit position
+pub const SEQUENCE_SHIFT: u16 = GENERATOR_SHIFT + GENERATOR_LENGTH;
+/// Timestamp bit position
+pub const TIMESTAMP_SHIFT: u16 = SEQUENCE_SHIFT + SEQUENCE_LENGTH;
+
+pub const GENERATOR_MASK: u64 = low_bits(GENERATOR_LENGTH as u64) << GENERATOR_SHIFT;
+pub const SEQUENCE_MASK: u64 = low_bits(SEQUENCE_LENGTH as u64) << SEQUENCE_SHIFT;
+pub const TIMESTAMP_MASK: u64 = low_bits(TIMESTAMP_LENGTH) << TIMESTAMP_SHIFT;
+/// Reserved high bit, see `FID::with_parity`
+pub const PARITY_MASK: u64 = 1 << 63;
+/// Version tag, shares reserved high bit with parity, see `FID::version`
+pub const VERSION_MASK: u64 = PARITY_MASK;
+/// Max layout version
+pub const VERSION_MAX: u8 = 1;
+
+/// Max generator value
+pub const GENERATOR_MAX: u16 = low_bits(GENERATOR_LENGTH as u64) as u16;
+/// Max sequence value
+pub const SEQUENCE_MAX: u16 = low_bits(SEQUENCE_LENGTH as u64) as u16;
+/// Max timestamp value
+pub const TIMESTAMP_MAX: u64 = low_bits(TIMESTAMP_LENGTH);
+
+/// FID128 generator length (in bits), at most 32
+pub const GENERATOR128_LENGTH: u32 = 32;
+/// FID128 sequence length (in bits), at most 32
+pub const SEQUENCE128_LENGTH: u32 = 32;
+/// FID128 timestamp length (in bits), at most 64
+pub const TIMESTAMP128_LENGTH: u32 = 64;
+
+// FID128 layout must fit u128, every field must fit its getter type
+const _: () = assert!(GENERATOR128_LENGTH + SEQUENCE128_LENGTH + TIMESTAMP128_LENGTH <= 128);
+const _: () = assert!(GENERATOR128_LENGTH <= 32 && SEQUENCE128_LENGTH <= 32);
+const _: () = assert!(TIMESTAMP128_LENGTH <= 64);
+
+/// Max FID128 generator value
+pub const GENERATOR128_MAX: u32 = low_bits(GENERATOR128_LENGTH as u64) as u32;
+/// Max FID128 sequence value
+pub const SEQUENCE128_MAX: u32 = low_bits(SEQUENCE128_LENGTH as u64) as u32;
+/// Max FID128 timestamp value
+pub const TIMESTAMP128_MAX: u64 = low_bits(TIMESTAMP128_LENGTH as u64);
+
 /// Contants for tests
 #[cfg(test)]
 pub(crate) mod test_constants {
@@ -33,8 +100,40 @@ pub(crate) mod test_constants {
     pub const B64: &[u8; 11] = b"Pm9rf79L4cw";
 }
 
-// Don't change next constants
-pub const GENERATOR_MASK: u64 = ((1 << GENERATOR_LENGTH) - 1);
-pub const SEQUENCE_MASK: u64 = ((1 << SEQUENCE_LENGTH) - 1) << GENERATOR_LENGTH;
-pub const TIMESTAMP_MASK: u64 =
-    ((1 << TIMESTAMP_LENGTH) - 1) << (GENERATOR_LENGTH + SEQUENCE_LENGTH);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn max_values() {
+        assert_eq!(GENERATOR_MAX as u64, GENERATOR_MASK);
+        assert_eq!((SEQUENCE_MAX as u64) << GENERATOR_LENGTH, SEQUENCE_MASK);
+        assert_eq!(TIMESTAMP_MAX << (GENERATOR_LENGTH + SEQUENCE_LENGTH), TIMESTAMP_MASK);
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(GENERATOR_MASK.trailing_zeros(), u32::from(GENERATOR_SHIFT));
+        assert_eq!(SEQUENCE_MASK.trailing_zeros(), u32::from(SEQUENCE_SHIFT));
+        assert_eq!(TIMESTAMP_MASK.trailing_zeros(), u32::from(TIMESTAMP_SHIFT));
+        assert_eq!(u64::from(TIMESTAMP_SHIFT) + TIMESTAMP_LENGTH, 63);
+    }
+
+    #[test]
+    fn low_bits() {
+        assert_eq!(super::low_bits(0), 0);
+        assert_eq!(super::low_bits(1), 1);
+        assert_eq!(super::low_bits(42), (1 << 42) - 1);
+        assert_eq!(super::low_bits(63), u64::MAX >> 1);
+        assert_eq!(super::low_bits(64), u64::MAX);
+    }
+
+    #[test]
+    fn masks_disjoint() {
+        assert_eq!(GENERATOR_MASK & SEQUENCE_MASK, 0);
+        assert_eq!((GENERATOR_MASK | SEQUENCE_MASK) & TIMESTAMP_MASK, 0);
+        let used = GENERATOR_MASK | SEQUENCE_MASK | TIMESTAMP_MASK;
+        let length = GENERATOR_LENGTH as u64 + SEQUENCE_LENGTH as u64 + TIMESTAMP_LENGTH;
+        assert_eq!(used, super::low_bits(length));
+    }
+}
@@ -1,8 +1,16 @@
 //! Flower identificator generator
 use std::cmp;
+use std::env;
+use std::str;
 use std::time;
 use std::thread;
+use std::hint;
+use std::fmt;
+use std::any::Any;
+use std::sync::{Arc, Mutex};
 use id::FID;
+use id_high::GeneratorHighFID;
+pub use id::Resolution;
 use config as cfg;
 #[cfg(not(test))]
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -14,18 +22,210 @@ use {Error, Result};
 /// Flower identificator generator
 #[derive(Debug, Clone)]
 pub struct FIDGenerator {
-    generator: u16,
-    timestamp_offset: i64,
-    timestamp_last: u64,
-    sequence: u16,
-    wait_sequence: bool,
-    timestamp_in_seconds: bool,
+    pub(crate) generator: u16,
+    pub(crate) timestamp_offset: i64,
+    pub(crate) timestamp_last: u64,
+    pub(crate) sequence: u16,
+    pub(crate) wait_sequence: bool,
+    pub(crate) resolution: Resolution,
+    pub(crate) monotonic: bool,
+    pub(crate) sequence_start: Option<SequenceSourceHandle>,
+    pub(crate) timestamp_overflow: TimestampOverflowPolicy,
+    pub(crate) wait_spin: u32,
+    pub(crate) clock_resolution: u64,
+    pub(crate) overflow_policy: Option<OverflowPolicyHandle>,
+    pub(crate) parity_bit: bool,
+    pub(crate) version: u8,
+    pub(crate) max_rate: Option<u32>,
+    pub(crate) max_future_skew: Option<time::Duration>,
+    /// monotonic time and raw clock timestamp of last id, see `max_future_skew`
+    pub(crate) skew_reference: Option<(time::Instant, u64)>,
+    pub(crate) rate_second: u64,
+    pub(crate) rate_count: u32,
+    pub(crate) wait_count: u64,
+}
+
+/// Max spin count of `FIDGeneratorBuilder::wait_spin`
+pub const WAIT_SPIN_MAX: u32 = 1 << 20;
+
+/// Generator behaviour when timestamp doesn't fit `TIMESTAMP_LENGTH` bits
+///
+/// See `FIDGeneratorBuilder::on_timestamp_overflow`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimestampOverflowPolicy {
+    /// return `Error::TimestampOverflow` (default)
+    Error,
+    /// clamp timestamp to max value and keep incrementing sequence,
+    /// `Error::SequenceOverflow` when sequence is exhausted
+    Saturate,
+    /// keep low `TIMESTAMP_LENGTH` bits of timestamp
+    ///
+    /// **DANGEROUS** ids generated after wrap are less than previous ones
+    /// and can collide with ids generated one window (~139 years) ago
+    Wrap,
+}
+
+/// Sequence overflow strategy
+///
+/// Called by `FIDGenerator::next` when sequence of current timestamp is exhausted,
+/// overrides `wait_sequence`. While the policy is running `gen` has no policy,
+/// so nested `gen.next()` falls back to `wait_sequence` behaviour.
+/// See `FIDGeneratorBuilder::overflow_policy`
+///
+/// # Examples
+/// ```
+/// use flowerid::{FID, Result};
+/// use flowerid::generator::*;
+///
+/// // borrow sequence space of the next timestamp,
+/// // monotonic mode keeps generating while clock is behind borrowed timestamp
+/// struct Borrow;
+/// impl OverflowPolicy for Borrow {
+///     fn on_overflow(&mut self, gen: &mut FIDGenerator) -> Result<FID> {
+///         let timestamp = gen.snapshot().timestamp_last + 1;
+///         gen.next_at(timestamp)
+///     }
+/// }
+///
+/// let mut gen = FIDGeneratorBuilder::new(0x12c)
+///     .overflow_policy(Borrow)
+///     .monotonic(true)
+///     .build()
+///     .unwrap();
+/// for _ in 0..4096 {
+///     gen.next().unwrap();
+/// }
+/// ```
+pub trait OverflowPolicy {
+    /// Handle sequence overflow of `gen`, result is returned by `gen.next()`
+    fn on_overflow(&mut self, gen: &mut FIDGenerator) -> Result<FID>;
+}
+
+/// Wait next timestamp on sequence overflow, alike `wait_sequence(true)`
+///
+/// Keeps waiting until sequence is available regardless of `wait_sequence`.
+/// Generator handles this policy in place, without locking shared policy.
+#[derive(Debug, Clone, Copy)]
+pub struct WaitPolicy;
+
+impl OverflowPolicy for WaitPolicy {
+    fn on_overflow(&mut self, gen: &mut FIDGenerator) -> Result<FID> {
+        let policy = gen.overflow_policy.replace(OverflowPolicyHandle::Wait);
+        let result = gen.next_unlimited(None);
+        gen.overflow_policy = policy;
+        result
+    }
+}
+
+/// Return `Error::SequenceOverflow` on sequence overflow, alike `wait_sequence(false)`
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorPolicy;
+
+impl OverflowPolicy for ErrorPolicy {
+    fn on_overflow(&mut self, gen: &mut FIDGenerator) -> Result<FID> {
+        Err(Error::SequenceOverflow(gen.sequence))
+    }
+}
+
+/// Shared policy, generator clones use the same policy instance
+#[derive(Clone)]
+pub(crate) enum OverflowPolicyHandle {
+    /// `WaitPolicy`, stateless so it needs no lock
+    Wait,
+    Custom(Arc<Mutex<dyn OverflowPolicy + Send>>),
+}
+
+impl fmt::Debug for OverflowPolicyHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OverflowPolicy")
+    }
+}
+
+/// Shared sequence start source, generator clones use the same source
+#[derive(Clone)]
+pub(crate) struct SequenceSourceHandle(Arc<Mutex<dyn FnMut() -> u16 + Send>>);
+
+impl fmt::Debug for SequenceSourceHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SequenceSource")
+    }
+}
+
+/// Generator state snapshot
+///
+/// See `FIDGenerator::snapshot` and `FIDGeneratorBuilder::from_state`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneratorState {
+    /// generator id
+    pub generator: u16,
+    /// last used timestamp
+    pub timestamp_last: u64,
+    /// last used sequence
+    pub sequence: u16,
+    /// timestamp offset (in seconds)
+    pub timestamp_offset: i64,
+    /// timestamp unit
+    pub resolution: Resolution,
+}
+
+/// Generator status report
+///
+/// See `FIDGenerator::status`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneratorStatus {
+    /// generator id
+    pub generator: u16,
+    /// timestamp unit
+    pub resolution: Resolution,
+    /// timestamp offset (in seconds)
+    pub timestamp_offset: i64,
+    /// last used timestamp
+    pub timestamp_last: u64,
+    /// last used sequence
+    pub sequence: u16,
+    /// wait next timestamp if sequence is overflowed
+    pub wait_sequence: bool,
 }
 
 /// Generator configuration builder
 #[derive(Debug, Clone)]
 pub struct FIDGeneratorBuilder(FIDGenerator);
 
+/// Derive generator id from arbitrary seed (hostname, MAC address, pod name, ...)
+///
+/// Seed is hashed with 64-bit FNV-1a and reduced to `GENERATOR_LENGTH` bits.
+/// **WARNING** generator space is small: distinct seeds can collide, for 1024 ids
+/// a collision is likely already among ~40 seeds, assign ids explicitly if that matters
+///
+/// # Examples
+/// ```
+/// use flowerid::generator::derive_generator_id;
+/// assert_eq!(derive_generator_id(b"node-1"), derive_generator_id(b"node-1"));
+/// assert!(derive_generator_id(b"node-1") < 1 << 10);
+/// ```
+pub fn derive_generator_id(seed: &[u8]) -> u16 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for x in seed {
+        hash ^= *x as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    (hash % (u64::from(cfg::GENERATOR_MAX) + 1)) as u16
+}
+
+impl Default for FIDGeneratorBuilder {
+    /// alike `FIDGeneratorBuilder::new(0)`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let gen = FIDGeneratorBuilder::default().sequence(5).build().unwrap();
+    /// assert_eq!(gen.generator_id(), 0);
+    /// ```
+    fn default() -> FIDGeneratorBuilder {
+        FIDGeneratorBuilder::new(0)
+    }
+}
+
 impl FIDGeneratorBuilder {
     /// Create builder
     ///
@@ -43,15 +243,86 @@ impl FIDGeneratorBuilder {
     /// ```
     pub fn new(generator: u16) -> FIDGeneratorBuilder {
         FIDGeneratorBuilder(FIDGenerator {
-            generator: generator,
+            generator,
             timestamp_offset: cfg::gbuilder_defaults::TIMESTAMP_OFFSET,
             timestamp_last: 0,
             sequence: 0,
             wait_sequence: cfg::gbuilder_defaults::WAIT_SEQUENCE,
-            timestamp_in_seconds: cfg::gbuilder_defaults::TIMESTAMP_IN_SECONDS,
+            resolution: Resolution::default(),
+            monotonic: cfg::gbuilder_defaults::MONOTONIC,
+            sequence_start: None,
+            timestamp_overflow: TimestampOverflowPolicy::Error,
+            wait_spin: cfg::gbuilder_defaults::WAIT_SPIN,
+            clock_resolution: cfg::gbuilder_defaults::CLOCK_RESOLUTION_MS,
+            overflow_policy: None,
+            parity_bit: cfg::gbuilder_defaults::PARITY_BIT,
+            version: cfg::gbuilder_defaults::VERSION,
+            max_rate: None,
+            max_future_skew: None,
+            skew_reference: None,
+            rate_second: 0,
+            rate_count: 0,
+            wait_count: 0,
         })
     }
 
+    /// Create builder with generator id derived from seed
+    ///
+    /// alike `FIDGeneratorBuilder::new(derive_generator_id(seed))`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// let gen = FIDGeneratorBuilder::from_seed(b"worker-7f9c").build().unwrap();
+    /// ```
+    pub fn from_seed(seed: &[u8]) -> FIDGeneratorBuilder {
+        FIDGeneratorBuilder::new(derive_generator_id(seed))
+    }
+
+    /// Create builder with random generator id taken from `rng`
+    ///
+    /// Result of `rng` is masked to `GENERATOR_LENGTH` bits.
+    /// **WARNING** generator space is only 1024 values: two of `n` random
+    /// generators collide with probability about `n * n / 2048`
+    /// (~50% for 38 generators), prefer assigned ids for long-lived workers
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// use std::collections::hash_map::RandomState;
+    /// use std::hash::{BuildHasher, Hasher};
+    /// let gen = FIDGeneratorBuilder::random_generator(|| {
+    ///     RandomState::new().build_hasher().finish() as u16
+    /// }).build().unwrap();
+    /// ```
+    pub fn random_generator<R: FnMut() -> u16>(mut rng: R) -> FIDGeneratorBuilder {
+        FIDGeneratorBuilder::new(rng() & cfg::GENERATOR_MAX)
+    }
+
+    /// Create builder restoring generator state
+    ///
+    /// Restored generator never goes backward from the snapshotted `timestamp_last`,
+    /// if system time hasn't caught up yet `next` fails with `Error::SysTimeIsInPast`
+    /// (unless monotonic mode is set)
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c).build().unwrap();
+    /// let fid = gen.next().unwrap();
+    /// let state = gen.snapshot();
+    /// let mut gen = FIDGeneratorBuilder::from_state(state).build().unwrap();
+    /// assert!(gen.next().unwrap() > fid);
+    /// ```
+    pub fn from_state(state: GeneratorState) -> FIDGeneratorBuilder {
+        let mut builder = FIDGeneratorBuilder::new(state.generator);
+        builder.0.timestamp_last = state.timestamp_last;
+        builder.0.sequence = state.sequence;
+        builder.0.timestamp_offset = state.timestamp_offset;
+        builder.0.resolution = state.resolution;
+        builder
+    }
+
     /// Set timestamp last timestamp
     pub fn timestamp_last(mut self, val: u64) -> FIDGeneratorBuilder {
         self.0.timestamp_last = val;
@@ -59,14 +330,41 @@ impl FIDGeneratorBuilder {
     }
 
     /// Set timestamp in seconds
-    pub fn timestamp_in_seconds(mut self) -> FIDGeneratorBuilder {
-        self.0.timestamp_in_seconds = true;
-        self
+    pub fn timestamp_in_seconds(self) -> FIDGeneratorBuilder {
+        self.resolution(Resolution::Seconds)
     }
 
     /// Set timestamp in seconds
-    pub fn timestamp_in_milliseconds(mut self) -> FIDGeneratorBuilder {
-        self.0.timestamp_in_seconds = false;
+    pub fn timestamp_in_milliseconds(self) -> FIDGeneratorBuilder {
+        self.resolution(Resolution::Millis)
+    }
+
+    /// Set timestamp in microseconds
+    ///
+    /// **WARNING** timestamp window is only ~50.9 days, `timestamp_offset` must be
+    /// recent (`build` fails with `Error::InvalidOffset` otherwise) and ids
+    /// overflow soon after. Consider it for short-lived high-rate generators only.
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// use std::time::{SystemTime, UNIX_EPOCH};
+    /// let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    /// let gen = FIDGeneratorBuilder::new(0x12c)
+    ///     .timestamp_offset(-now)
+    ///     .timestamp_in_microseconds()
+    ///     .build();
+    /// assert!(gen.is_ok());
+    /// ```
+    pub fn timestamp_in_microseconds(self) -> FIDGeneratorBuilder {
+        self.resolution(Resolution::Micros)
+    }
+
+    /// Set timestamp resolution
+    ///
+    /// see `Resolution` for timestamp window of each unit
+    pub fn resolution(mut self, val: Resolution) -> FIDGeneratorBuilder {
+        self.0.resolution = val;
         self
     }
 
@@ -82,6 +380,35 @@ impl FIDGeneratorBuilder {
         self
     }
 
+    /// Set timestamp offset from custom epoch
+    ///
+    /// Offset is truncated to whole seconds
+    ///
+    /// # Failures
+    /// `Error::SysTimeIsInPast` if `custom_epoch` is in the future
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use flowerid::generator::*;
+    /// let epoch = UNIX_EPOCH + Duration::from_secs(1483228800);
+    /// let gen = FIDGeneratorBuilder::new(0).epoch(epoch).unwrap().build().unwrap();
+    /// ```
+    pub fn epoch(mut self, custom_epoch: time::SystemTime) -> Result<FIDGeneratorBuilder> {
+        let offset = match custom_epoch.duration_since(time::UNIX_EPOCH) {
+            Ok(x) => -(x.as_secs() as i64),
+            Err(x) => x.duration().as_secs() as i64,
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| Error::SysTimeIsInPast)?;
+        if -offset > now.as_secs() as i64 {
+            return Err(Error::SysTimeIsInPast);
+        }
+        self.0.timestamp_offset = offset;
+        Ok(self)
+    }
+
     /// Set wait sequence
     ///
     /// If wait_sequence is true and sequence overflowed, generator wait till next timetamp has been generated
@@ -90,6 +417,193 @@ impl FIDGeneratorBuilder {
         self
     }
 
+    /// Limit number of ids generated by `next` per second (unlimited by default)
+    ///
+    /// Once limit of current second is reached `next` waits next second if
+    /// `wait_sequence` is true, otherwise returns `Error::RateLimited`.
+    /// `next_at`, `SharedGenerator` and `AtomicGenerator` ignore the limit.
+    /// Zero limit is rejected with `Error::InvalidRateLimit` by `build`.
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c)
+    ///     .max_rate_per_second(1000)
+    ///     .build()
+    ///     .unwrap();
+    /// gen.next().unwrap();
+    /// ```
+    pub fn max_rate_per_second(mut self, val: u32) -> FIDGeneratorBuilder {
+        self.0.max_rate = Some(val);
+        self
+    }
+
+    /// Set busy-spin count of sequence overflow wait
+    ///
+    /// Waiting for next timestamp spins `spins` times (`std::hint::spin_loop`)
+    /// before falling back to sleeping, burns CPU for lower latency when next
+    /// timestamp is imminent. Capped to `WAIT_SPIN_MAX`, 0 is sleep only.
+    pub fn wait_spin(mut self, spins: u32) -> FIDGeneratorBuilder {
+        self.0.wait_spin = cmp::min(spins, WAIT_SPIN_MAX);
+        self
+    }
+
+    /// Set assumed system clock resolution (in milliseconds)
+    ///
+    /// Waiting for next timestamp sleeps at least `val` ms per iteration instead of 1ms,
+    /// use on platforms with coarse clock (e.g. 15ms) to avoid wasting CPU on polling
+    pub fn assume_clock_resolution_ms(mut self, val: u64) -> FIDGeneratorBuilder {
+        self.0.clock_resolution = val;
+        self
+    }
+
+    /// Set sequence overflow policy
+    ///
+    /// Overrides `wait_sequence`, see `OverflowPolicy`. Custom policy is locked
+    /// while it runs, so clones overflowing at once are serialized;
+    /// `WaitPolicy` is handled without lock.
+    /// `SharedGenerator` and `AtomicGenerator` ignore the policy.
+    pub fn overflow_policy<P: OverflowPolicy + Send + 'static>(
+        mut self,
+        policy: P,
+    ) -> FIDGeneratorBuilder {
+        let handle = if (&policy as &dyn Any).is::<WaitPolicy>() {
+            OverflowPolicyHandle::Wait
+        } else {
+            OverflowPolicyHandle::Custom(Arc::new(Mutex::new(policy)))
+        };
+        self.0.overflow_policy = Some(handle);
+        self
+    }
+
+    /// Set monotonic mode
+    ///
+    /// If monotonic is true and system time went backward, generator keep using last timestamp
+    /// and advance sequence instead of `Error::SysTimeIsInPast`.
+    /// On sequence overflow while clock is behind, waiting generator steps to the next
+    /// timestamp instead of stalling until clock catches up, so timestamps of generated ids
+    /// can run ahead of real time under sustained backward drift.
+    pub fn monotonic(mut self, val: bool) -> FIDGeneratorBuilder {
+        self.0.monotonic = val;
+        self
+    }
+
+    /// Set parity bit mode
+    ///
+    /// If true, reserved high bit of every generated id is set to parity of
+    /// lower bits, see `FID::with_parity` and `FID::verify_parity`.
+    /// **WARNING** such ids are not ordered by raw `u64` value.
+    pub fn parity_bit(mut self, val: bool) -> FIDGeneratorBuilder {
+        self.0.parity_bit = val;
+        self
+    }
+
+    /// Set sanity bound of clock jumps ahead
+    ///
+    /// `next` fails with `Error::SysTimeTooFarAhead` if system time jumped ahead
+    /// more than `val`, so clock faults aren't mistaken for timestamp window
+    /// exhaustion. Jump is measured against time of last id advanced by
+    /// monotonic clock (`Instant`), so idle pauses between ids never trip it.
+    /// Bound isn't checked until first id (or after `reset`).
+    /// `next_at`, `SharedGenerator` and `AtomicGenerator` don't check it.
+    pub fn max_future_skew(mut self, val: time::Duration) -> FIDGeneratorBuilder {
+        self.0.max_future_skew = Some(val);
+        self
+    }
+
+    /// Set layout version stamped into every generated id
+    ///
+    /// Version is stored in reserved high bit, see `FID::version`.
+    /// Non-zero version can't be combined with `parity_bit`.
+    pub fn version(mut self, val: u8) -> FIDGeneratorBuilder {
+        self.0.version = val;
+        self
+    }
+
+    /// Set random sequence start
+    ///
+    /// If true, sequence of every new timestamp starts from random value instead of 0,
+    /// so consecutive ids aren't trivially enumerable.
+    /// **WARNING** sequence still overflows at `1 << SEQUENCE_LENGTH`,
+    /// high random start leaves less ids for that timestamp.
+    pub fn random_sequence_start(mut self, val: bool) -> FIDGeneratorBuilder {
+        self.0.sequence_start = if val {
+            Some(SequenceSourceHandle(Arc::new(Mutex::new(random_sequence_start))))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Set sequence start source
+    ///
+    /// alike `random_sequence_start(true)` with custom randomness,
+    /// returned value is wrapped within sequence space.
+    /// Source may keep state (e.g. seeded RNG), generator clones share it
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    ///
+    /// let mut seed = 0x900u16;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c)
+    ///     .sequence_source(move || {
+    ///         seed = seed.wrapping_mul(31).wrapping_add(7);
+    ///         seed
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(gen.next().unwrap().sequence() <= 0x7ff);
+    /// ```
+    pub fn sequence_source<F>(mut self, source: F) -> FIDGeneratorBuilder
+    where
+        F: FnMut() -> u16 + Send + 'static,
+    {
+        self.0.sequence_start = Some(SequenceSourceHandle(Arc::new(Mutex::new(source))));
+        self
+    }
+
+    /// Set timestamp overflow policy
+    ///
+    /// see `TimestampOverflowPolicy`
+    pub fn on_timestamp_overflow(mut self, policy: TimestampOverflowPolicy) -> FIDGeneratorBuilder {
+        self.0.timestamp_overflow = policy;
+        self
+    }
+
+    /// Check configuration without building generator
+    ///
+    /// Runs the same checks as `FIDGenerator::new`, builder is kept for fixing and retrying
+    ///
+    /// # Failures
+    /// alike `FIDGenerator::new`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::Error;
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let builder = FIDGeneratorBuilder::new(1 << 10);
+    /// assert_eq!(builder.validate().unwrap_err(), Error::GeneratorOverflow(1 << 10));
+    /// assert!(FIDGeneratorBuilder::new(0x12c).validate().is_ok());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        if self.0.generator > cfg::GENERATOR_MAX {
+            Err(Error::GeneratorOverflow(self.0.generator))
+        } else if self.0.sequence > cfg::SEQUENCE_MAX {
+            Err(Error::SequenceOverflow(self.0.sequence))
+        } else if self.0.timestamp_last > cfg::TIMESTAMP_MAX {
+            Err(Error::TimestampOverflow(self.0.timestamp_last))
+        } else if self.0.version > cfg::VERSION_MAX {
+            Err(Error::InvalidVersion(self.0.version))
+        } else if self.0.version != 0 && self.0.parity_bit {
+            Err(Error::ReservedBitConflict)
+        } else if self.0.max_rate == Some(0) {
+            Err(Error::InvalidRateLimit)
+        } else {
+            self.0.check_offset()
+        }
+    }
+
     /// Build `FIDGenerator`
     ///
     /// alike `FIDGenerator::new(self)`
@@ -105,16 +619,80 @@ impl FIDGenerator {
     /// `Error::GeneratorOverflow`
     /// `Error::SequenceOverflow`
     /// `Error::TimestampOverflow`
+    /// `Error::InvalidOffset` - offset combined with current time doesn't fit timestamp field
+    /// `Error::InvalidRateLimit` - zero `max_rate_per_second`
     pub fn new(cfg: FIDGeneratorBuilder) -> Result<FIDGenerator> {
-        if cfg.0.generator >= 1 << cfg::GENERATOR_LENGTH {
-            Err(Error::GeneratorOverflow(cfg.0.generator))
-        } else if cfg.0.sequence >= 1 << cfg::SEQUENCE_LENGTH {
-            Err(Error::SequenceOverflow(cfg.0.sequence))
-        } else if cfg.0.timestamp_last >= 1 << cfg::TIMESTAMP_LENGTH {
-            Err(Error::TimestampOverflow(cfg.0.timestamp_last))
-        } else {
-            Ok(cfg.0)
+        cfg.validate()?;
+        Ok(cfg.0)
+    }
+
+    /// Create new generator configured by environment variables
+    ///
+    /// | variable               | value                           | default                |
+    /// | ---------------------- | ------------------------------- | ---------------------- |
+    /// | `FID_GENERATOR_ID`     | generator id                    | required               |
+    /// | `FID_TIMESTAMP_OFFSET` | offset (in seconds)             | `TIMESTAMP_OFFSET`     |
+    /// | `FID_RESOLUTION`       | `seconds`, `millis` or `micros` | `TIMESTAMP_IN_SECONDS` |
+    /// | `FID_WAIT_SEQUENCE`    | `true`/`false` or `1`/`0`       | `WAIT_SEQUENCE`        |
+    ///
+    /// defaults are `config::gbuilder_defaults`
+    ///
+    /// # Failures
+    /// `Error::InvalidEnvVar` variable is missing or unparsable
+    /// alike `new`
+    pub fn from_env() -> Result<FIDGenerator> {
+        fn var(name: &'static str) -> Result<Option<String>> {
+            match env::var(name) {
+                Ok(x) => Ok(Some(x)),
+                Err(env::VarError::NotPresent) => Ok(None),
+                Err(env::VarError::NotUnicode(_)) => Err(Error::InvalidEnvVar(name)),
+            }
+        }
+        fn parse<T: str::FromStr>(name: &'static str) -> Result<Option<T>> {
+            match var(name)? {
+                Some(x) => x.trim().parse().map(Some).map_err(|_| Error::InvalidEnvVar(name)),
+                None => Ok(None),
+            }
+        }
+
+        let generator = parse("FID_GENERATOR_ID")?.ok_or(Error::InvalidEnvVar("FID_GENERATOR_ID"))?;
+        let mut builder = FIDGeneratorBuilder::new(generator);
+        if let Some(x) = parse("FID_TIMESTAMP_OFFSET")? {
+            builder = builder.timestamp_offset(x);
+        }
+        if let Some(x) = var("FID_RESOLUTION")? {
+            builder = builder.resolution(match x.trim() {
+                "seconds" => Resolution::Seconds,
+                "millis" => Resolution::Millis,
+                "micros" => Resolution::Micros,
+                _ => return Err(Error::InvalidEnvVar("FID_RESOLUTION")),
+            });
+        }
+        if let Some(x) = var("FID_WAIT_SEQUENCE")? {
+            builder = builder.wait_sequence(match x.trim() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => return Err(Error::InvalidEnvVar("FID_WAIT_SEQUENCE")),
+            });
         }
+        builder.build()
+    }
+
+    /// Create new generator with default configuration
+    ///
+    /// alike `FIDGeneratorBuilder::new(generator).build()`
+    ///
+    /// # Failures
+    /// alike `new`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGenerator;
+    /// let mut gen = FIDGenerator::with_id(0x12c).unwrap();
+    /// assert_eq!(gen.next().unwrap().generator(), 0x12c);
+    /// ```
+    pub fn with_id(generator: u16) -> Result<FIDGenerator> {
+        FIDGenerator::new(FIDGeneratorBuilder::new(generator))
     }
 
     /// Generate next id
@@ -130,96 +708,709 @@ impl FIDGenerator {
     /// let mut gen = FIDGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
     /// println!("{}", gen.next().unwrap());
     /// ```
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<FID> {
-        let timestamp = self.new_timestamp()?;
-
-        match timestamp.cmp(&self.timestamp_last) {
-            cmp::Ordering::Less => Err(Error::SysTimeIsInPast),
-            cmp::Ordering::Greater => self.next_timestamp(timestamp),
-            cmp::Ordering::Equal => self.next_sequence(timestamp),
-        }
+        self.next_waited(None)
     }
 
-    fn new_timestamp(&self) -> Result<u64> {
-        let convert_time = |time: &time::Duration| -> u64 {
-            if self.timestamp_in_seconds {
-                time.as_secs()
-            } else {
-                time.as_secs() * 1000 + (time.subsec_nanos() / 1000 / 1000) as u64
-            }
-        };
+    /// Generate next id as packed `u64`
+    ///
+    /// alike `next().map(u64::from)`, `FID` is a plain `u64` wrapper so no extra work is done
+    ///
+    /// # Failures
+    /// alike `next`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::FID;
+    /// use flowerid::generator::*;
+    /// let mut gen = FIDGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
+    /// let id = gen.next_u64().unwrap();
+    /// assert_eq!(FID::from(id).generator(), 0x12c);
+    /// ```
+    pub fn next_u64(&mut self) -> Result<u64> {
+        self.next_waited(None).map(From::from)
+    }
 
-        let mut offset = UNIX_EPOCH;
-        if self.timestamp_offset < 0 {
-            offset += time::Duration::from_secs(self.timestamp_offset.abs() as u64);
-        } else {
-            offset -= time::Duration::from_secs(self.timestamp_offset.abs() as u64);
-        }
+    /// Generate next id in generator-high layout
+    ///
+    /// For prefix routing by generator (shard), see `GeneratorHighFID`
+    ///
+    /// # Failures
+    /// alike `next`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c).build().unwrap();
+    /// let fid = gen.next_generator_high().unwrap();
+    /// assert_eq!(fid.generator(), 0x12c);
+    /// assert_eq!(u64::from(fid) >> 53, 0x12c);
+    /// ```
+    pub fn next_generator_high(&mut self) -> Result<GeneratorHighFID> {
+        self.next_waited(None).map(From::from)
+    }
 
-        let sys_time = SystemTime::now();
-        if sys_time < offset {
-            return Err(Error::SysTimeIsInPast);
+    /// Generate next id for given timestamp instead of current time
+    ///
+    /// For backfilling historical data, sequence is managed alike `next`.
+    /// Timestamp is in generator units (offset applied), see `FID::timestamp`.
+    /// Timestamps older than last one are rejected, or replaced with last one in monotonic mode.
+    ///
+    /// # Failures
+    /// `Error::TimestampOverflow`
+    /// `Error::SysTimeIsInPast` timestamp is older than last one
+    /// `Error::SequenceOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// let mut gen = FIDGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
+    /// let fid = gen.next_at(0x204dc595637).unwrap();
+    /// assert_eq!(fid.components(), (0x204dc595637, 0, 0x12c));
+    /// assert_eq!(gen.next_at(0x204dc595637).unwrap().sequence(), 1);
+    /// assert!(gen.next_at(0x204dc595636).is_err());
+    /// ```
+    pub fn next_at(&mut self, timestamp: u64) -> Result<FID> {
+        if timestamp > cfg::TIMESTAMP_MAX {
+            return Err(Error::TimestampOverflow(timestamp));
         }
-
-        let timestamp = sys_time
-            .duration_since(offset)
-            .map_err(|_| Error::SysTimeIsInPast)?;
-        let timestamp = convert_time(&timestamp);
-        if timestamp >= (1 << cfg::TIMESTAMP_LENGTH) {
-            Err(Error::TimestampOverflow(timestamp))
+        let timestamp = match timestamp.cmp(&self.timestamp_last) {
+            cmp::Ordering::Less if self.monotonic => self.timestamp_last,
+            cmp::Ordering::Less => return Err(Error::SysTimeIsInPast),
+            cmp::Ordering::Greater => return self.next_timestamp(timestamp),
+            cmp::Ordering::Equal => timestamp,
+        };
+        if self.sequence < cfg::SEQUENCE_MAX {
+            self.next_sequence(timestamp)
         } else {
-            Ok(timestamp)
+            Err(Error::SequenceOverflow(self.sequence))
         }
     }
 
-    fn next_timestamp(&mut self, timestamp: u64) -> Result<FID> {
-        self.timestamp_last = timestamp;
-        self.sequence = 0;
-        FID::new(timestamp, 0, self.generator)
+    /// Generate next id and return time spent waiting next timestamp
+    ///
+    /// Duration is zero unless sequence overflowed, see `wait_sequence`,
+    /// time spent in `overflow_policy` is included
+    ///
+    /// # Failures
+    /// alike `next`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// let mut gen = FIDGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
+    /// let (fid, waited) = gen.next_timed().unwrap();
+    /// println!("{} {:?}", fid, waited);
+    /// ```
+    pub fn next_timed(&mut self) -> Result<(FID, time::Duration)> {
+        let mut waited = time::Duration::from_secs(0);
+        let fid = self.next_waited(Some(&mut waited))?;
+        Ok((fid, waited))
     }
 
-    fn wait_next_timestamp(&self) -> Result<()> {
-        let start_time = SystemTime::now();
+    fn next_waited(&mut self, waited: Option<&mut time::Duration>) -> Result<FID> {
+        if self.max_rate.is_none() {
+            return self.next_unlimited(waited);
+        }
         loop {
-            if let Ok(duration_since) = SystemTime::now().duration_since(start_time) {
-                if self.timestamp_in_seconds {
-                    if duration_since.as_secs() > 0 {
-                        return Ok(());
-                    } else {
-                        thread::sleep(time::Duration::from_millis(10));
-                    }
-                } else {
-                    if duration_since.subsec_nanos() / 1000 / 1000 > 0 {
-                        return Ok(());
-                    } else {
-                        thread::sleep(time::Duration::from_millis(1));
-                    }
-                }
+            let timestamp = self.new_timestamp()?;
+            if !self.is_rate_limited(timestamp) {
+                let fid = self.next_unlimited(waited)?;
+                self.count_rate(fid.timestamp());
+                return Ok(fid);
+            } else if self.wait_sequence {
+                let per_second = self.resolution.per_second();
+                let left = per_second - timestamp % per_second;
+                thread::sleep(self.resolution.duration(left));
             } else {
-                return Err(Error::SysTimeIsInPast);
+                return Err(Error::RateLimited);
             }
         }
     }
 
-    fn next_sequence(&mut self, timestamp: u64) -> Result<FID> {
-        if (self.sequence + 1) >= (1 << cfg::SEQUENCE_LENGTH) {
-            if self.wait_sequence {
-                self.wait_next_timestamp()?;
-                return self.next();
+    fn next_unlimited(&mut self, waited: Option<&mut time::Duration>) -> Result<FID> {
+        let (fid, clock) = self.next_clocked(waited)?;
+        if self.max_future_skew.is_some() {
+            self.skew_reference = Some((time::Instant::now(), clock));
+        }
+        Ok(fid)
+    }
+
+    /// Generated id and raw clock timestamp it was generated at
+    fn next_clocked(&mut self, mut waited: Option<&mut time::Duration>) -> Result<(FID, u64)> {
+        loop {
+            let (clock, now) = self.new_timestamp_skew_checked()?;
+
+            let timestamp = match now.cmp(&self.timestamp_last) {
+                cmp::Ordering::Less if self.is_wrapped(now, self.timestamp_last) => {
+                    return self.next_timestamp(now).map(|x| (x, clock))
+                }
+                cmp::Ordering::Less if self.monotonic => self.timestamp_last,
+                cmp::Ordering::Less => return Err(Error::SysTimeIsInPast),
+                cmp::Ordering::Greater => return self.next_timestamp(now).map(|x| (x, clock)),
+                cmp::Ordering::Equal => now,
+            };
+
+            if self.sequence < cfg::SEQUENCE_MAX {
+                return self.next_sequence(timestamp).map(|x| (x, clock));
+            }
+            let wait = match self.overflow_policy.clone() {
+                Some(OverflowPolicyHandle::Custom(policy)) => {
+                    self.overflow_policy = None;
+                    let start = time::Instant::now();
+                    let result = policy
+                        .lock()
+                        .unwrap_or_else(|x| x.into_inner())
+                        .on_overflow(self);
+                    if let Some(ref mut waited) = waited {
+                        **waited += start.elapsed();
+                    }
+                    self.overflow_policy = Some(OverflowPolicyHandle::Custom(policy));
+                    return result.map(|x| (x, clock));
+                }
+                Some(OverflowPolicyHandle::Wait) => true,
+                None => self.wait_sequence,
+            };
+            if wait && self.can_step_ahead(now, timestamp) {
+                return self.next_timestamp(timestamp + 1).map(|x| (x, clock));
+            } else if wait && !self.is_saturated(timestamp) {
+                self.wait_count += 1;
+                if let Some(ref mut waited) = waited {
+                    let start = time::Instant::now();
+                    self.wait_next_timestamp()?;
+                    **waited += start.elapsed();
+                } else {
+                    self.wait_next_timestamp()?;
+                }
             } else {
                 return Err(Error::SequenceOverflow(self.sequence));
             }
-        } else {
-            self.sequence += 1;
-            return FID::new(timestamp, self.sequence, self.generator);
         }
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use config::test_constants::*;
+
+    /// Change generator id
+    ///
+    /// Keeps `timestamp_last` and resets sequence to 0
+    ///
+    /// # Failures
+    /// `Error::GeneratorOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// let mut gen = FIDGeneratorBuilder::new(0).build().unwrap();
+    /// gen.set_generator(0x12c).unwrap();
+    /// assert_eq!(gen.next().unwrap().generator(), 0x12c);
+    /// ```
+    pub fn set_generator(&mut self, generator: u16) -> Result<()> {
+        if generator > cfg::GENERATOR_MAX {
+            Err(Error::GeneratorOverflow(generator))
+        } else {
+            self.generator = generator;
+            self.sequence = 0;
+            Ok(())
+        }
+    }
+
+    /// Reset sequence and last timestamp to 0
+    ///
+    /// Keeps generator id, offset and resolution settings, doesn't read the clock
+    pub fn reset(&mut self) {
+        self.timestamp_last = 0;
+        self.sequence = 0;
+        self.skew_reference = None;
+    }
+
+    /// Reset sequence and last timestamp to given values
+    ///
+    /// # Failures
+    /// `Error::TimestampOverflow`
+    /// `Error::SequenceOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::*;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c).build().unwrap();
+    /// gen.reset_to(0x204dc595637, 0x4ac).unwrap();
+    /// ```
+    pub fn reset_to(&mut self, timestamp_last: u64, sequence: u16) -> Result<()> {
+        if timestamp_last > cfg::TIMESTAMP_MAX {
+            Err(Error::TimestampOverflow(timestamp_last))
+        } else if sequence > cfg::SEQUENCE_MAX {
+            Err(Error::SequenceOverflow(sequence))
+        } else {
+            self.timestamp_last = timestamp_last;
+            self.sequence = sequence;
+            Ok(())
+        }
+    }
+
+    /// Snapshot generator state for crash recovery
+    ///
+    /// See `FIDGeneratorBuilder::from_state`
+    pub fn snapshot(&self) -> GeneratorState {
+        GeneratorState {
+            generator: self.generator,
+            timestamp_last: self.timestamp_last,
+            sequence: self.sequence,
+            timestamp_offset: self.timestamp_offset,
+            resolution: self.resolution,
+        }
+    }
+
+    /// Time elapsed since fid generation
+    ///
+    /// fid is interpreted with generator offset and resolution
+    ///
+    /// # Failures
+    /// `Error::SysTimeIsInPast` fid timestamp is in the future
+    /// `Error::TimestampOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c).build().unwrap();
+    /// let fid = gen.next().unwrap();
+    /// assert!(gen.age_of(&fid).unwrap().as_secs() < 60);
+    /// ```
+    pub fn age_of(&self, fid: &FID) -> Result<time::Duration> {
+        let age = self
+            .new_timestamp()?
+            .checked_sub(fid.timestamp())
+            .ok_or(Error::SysTimeIsInPast)?;
+        Ok(self.resolution.duration(age))
+    }
+
+    /// Check two generators can produce same ids at the same time
+    ///
+    /// True if generator ids are equal and timestamp spaces coincide (same offset and resolution).
+    /// **WARNING** generators with equal generator id but different offset or resolution
+    /// still can produce equal ids at different moments, assign distinct generator ids
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let a = FIDGeneratorBuilder::new(0x47).build().unwrap();
+    /// let b = FIDGeneratorBuilder::new(0x47).build().unwrap();
+    /// let c = FIDGeneratorBuilder::new(0x48).build().unwrap();
+    /// assert!(a.collides_with(&b));
+    /// assert!(!a.collides_with(&c));
+    /// ```
+    pub fn collides_with(&self, other: &FIDGenerator) -> bool {
+        self.generator == other.generator
+            && self.timestamp_offset == other.timestamp_offset
+            && self.resolution == other.resolution
+    }
+
+    /// Current generator status
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let gen = FIDGeneratorBuilder::new(0x12c).build().unwrap();
+    /// let status = gen.status();
+    /// assert_eq!(status.generator, 0x12c);
+    /// println!("{:?}", status);
+    /// ```
+    pub fn status(&self) -> GeneratorStatus {
+        GeneratorStatus {
+            generator: self.generator,
+            resolution: self.resolution,
+            timestamp_offset: self.timestamp_offset,
+            timestamp_last: self.timestamp_last,
+            sequence: self.sequence,
+            wait_sequence: self.wait_sequence,
+        }
+    }
+
+    /// generator id getter
+    pub fn generator_id(&self) -> u16 {
+        self.generator
+    }
+
+    /// timestamp offset (in seconds) getter
+    pub fn offset(&self) -> i64 {
+        self.timestamp_offset
+    }
+
+    /// Check timestamp is in seconds
+    pub fn is_seconds(&self) -> bool {
+        self.resolution == Resolution::Seconds
+    }
+
+    /// timestamp resolution getter
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// timestamp offset (in seconds) getter
+    ///
+    /// alike `offset`
+    pub fn timestamp_offset(&self) -> i64 {
+        self.timestamp_offset
+    }
+
+    /// last used timestamp getter
+    pub fn timestamp_last(&self) -> u64 {
+        self.timestamp_last
+    }
+
+    /// last used sequence getter
+    pub fn current_sequence(&self) -> u16 {
+        self.sequence
+    }
+
+    /// wait_sequence getter, see `FIDGeneratorBuilder::wait_sequence`
+    pub fn wait_sequence(&self) -> bool {
+        self.wait_sequence
+    }
+
+    /// timestamp_in_seconds getter
+    ///
+    /// alike `is_seconds`
+    pub fn timestamp_in_seconds(&self) -> bool {
+        self.resolution == Resolution::Seconds
+    }
+
+    /// Number of ids left in the last used timestamp
+    ///
+    /// Sequence overflows (see `wait_sequence`) once it hits 0,
+    /// ids of newer timestamps are not limited by it
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c).build().unwrap();
+    /// gen.next_at(0x204dc595637).unwrap();
+    /// gen.next_at(0x204dc595637).unwrap();
+    /// assert_eq!(gen.remaining_sequence(), 2046);
+    /// ```
+    pub fn remaining_sequence(&self) -> u16 {
+        cfg::SEQUENCE_MAX - self.sequence
+    }
+
+    /// Predict id of `next` without advancing generator
+    ///
+    /// Never waits, runs the same clock, `max_future_skew` and rate limit checks as `next`.
+    /// Exact unless sequence start is random (`random_sequence_start`, `sequence_source`):
+    /// then sequence of id in new timestamp is reported as 0.
+    ///
+    /// # Failures
+    /// `Error::SysTimeIsInPast`
+    /// `Error::SysTimeTooFarAhead`
+    /// `Error::SequenceOverflow` `next` would wait, fail or run `overflow_policy`
+    /// `Error::RateLimited` `next` would wait or fail
+    /// `Error::TimestampOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// let mut gen = FIDGeneratorBuilder::new(0x12c).build().unwrap();
+    /// let peeked = gen.peek_next().unwrap();
+    /// assert!(gen.next().unwrap() >= peeked);
+    /// ```
+    pub fn peek_next(&self) -> Result<FID> {
+        let (_, timestamp) = self.new_timestamp_skew_checked()?;
+        if self.is_rate_limited(timestamp) {
+            return Err(Error::RateLimited);
+        }
+        let fid = match timestamp.cmp(&self.timestamp_last) {
+            cmp::Ordering::Less if self.is_wrapped(timestamp, self.timestamp_last) => {
+                FID::new(timestamp, 0, self.generator)?
+            }
+            cmp::Ordering::Less if !self.monotonic => return Err(Error::SysTimeIsInPast),
+            cmp::Ordering::Greater => FID::new(timestamp, 0, self.generator)?,
+            _ if self.sequence < cfg::SEQUENCE_MAX => {
+                FID::new(self.timestamp_last, self.sequence + 1, self.generator)?
+            }
+            _ => return Err(Error::SequenceOverflow(self.sequence)),
+        };
+        Ok(self.seal(fid))
+    }
+
+    /// Number of sequence overflows the generator has waited out
+    ///
+    /// Incremented every time `next` waits for the next timestamp,
+    /// see `FIDGeneratorBuilder::wait_sequence`
+    pub fn wait_count(&self) -> u64 {
+        self.wait_count
+    }
+
+    /// Reset `wait_count` to 0
+    pub fn reset_stats(&mut self) {
+        self.wait_count = 0;
+    }
+
+    fn check_offset(&self) -> Result<()> {
+        let max = cfg::TIMESTAMP_MAX;
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let timestamp = self.resolution.units(since_epoch) as i128
+            + self.timestamp_offset as i128 * self.resolution.per_second() as i128;
+        let overflow = self.timestamp_overflow == TimestampOverflowPolicy::Error;
+        if timestamp < 0 || (overflow && timestamp > max as i128) {
+            let timestamp = cmp::max(cmp::min(timestamp, i64::MAX as i128), i64::MIN as i128);
+            Err(Error::InvalidOffset(timestamp as i64, max))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn new_timestamp(&self) -> Result<u64> {
+        let timestamp = current_timestamp(self.timestamp_offset, self.resolution)?;
+        self.apply_overflow_policy(timestamp)
+    }
+
+    /// alike `new_timestamp` checking `max_future_skew` before overflow policy
+    ///
+    /// Returns (raw clock timestamp, timestamp after overflow policy)
+    fn new_timestamp_skew_checked(&self) -> Result<(u64, u64)> {
+        let clock = current_timestamp(self.timestamp_offset, self.resolution)?;
+        let reference = self.skew_reference;
+        if let (Some(skew), Some((instant, reference))) = (self.max_future_skew, reference) {
+            // system time expected from monotonic clock since last id
+            let expected = reference.saturating_add(self.resolution.units(instant.elapsed()));
+            if clock > expected.saturating_add(self.resolution.units(skew)) {
+                return Err(Error::SysTimeTooFarAhead);
+            }
+        }
+        Ok((clock, self.apply_overflow_policy(clock)?))
+    }
+
+    fn apply_overflow_policy(&self, timestamp: u64) -> Result<u64> {
+        if timestamp <= cfg::TIMESTAMP_MAX {
+            return Ok(timestamp);
+        }
+        match self.timestamp_overflow {
+            TimestampOverflowPolicy::Error => Err(Error::TimestampOverflow(timestamp)),
+            TimestampOverflowPolicy::Saturate => Ok(cfg::TIMESTAMP_MAX),
+            TimestampOverflowPolicy::Wrap => Ok(timestamp & cfg::TIMESTAMP_MAX),
+        }
+    }
+
+    /// timestamp is saturated and can't advance
+    pub(crate) fn is_saturated(&self, timestamp: u64) -> bool {
+        self.timestamp_overflow == TimestampOverflowPolicy::Saturate
+            && timestamp == cfg::TIMESTAMP_MAX
+    }
+
+    /// timestamp went more than half window backward, treat it as wrapped
+    pub(crate) fn is_wrapped(&self, timestamp: u64, timestamp_last: u64) -> bool {
+        self.timestamp_overflow == TimestampOverflowPolicy::Wrap
+            && timestamp_last - timestamp >= 1 << (cfg::TIMESTAMP_LENGTH - 1)
+    }
+
+    /// backward (not wrapped) timestamp is replaced with last one in monotonic mode
+    pub(crate) fn clamp_monotonic(&self, timestamp: u64, timestamp_last: u64) -> u64 {
+        let backward = timestamp < timestamp_last && !self.is_wrapped(timestamp, timestamp_last);
+        if self.monotonic && backward {
+            timestamp_last
+        } else {
+            timestamp
+        }
+    }
+
+    /// timestamp clamped ahead of clock can step further on sequence overflow
+    /// instead of waiting until clock catches up
+    pub(crate) fn can_step_ahead(&self, clock: u64, timestamp: u64) -> bool {
+        clock < timestamp && timestamp < cfg::TIMESTAMP_MAX
+    }
+
+    pub(crate) fn first_sequence(&self) -> u16 {
+        match self.sequence_start {
+            Some(ref source) => {
+                let mut source = source.0.lock().unwrap_or_else(|x| x.into_inner());
+                source() & cfg::SEQUENCE_MAX
+            }
+            None => 0,
+        }
+    }
+
+    fn next_timestamp(&mut self, timestamp: u64) -> Result<FID> {
+        self.timestamp_last = timestamp;
+        self.sequence = self.first_sequence();
+        FID::new(timestamp, self.sequence, self.generator).map(|x| self.seal(x))
+    }
+
+    pub(crate) fn wait_next_timestamp(&self) -> Result<()> {
+        wait_next_timestamp(self.resolution, self.wait_spin, self.clock_resolution)
+    }
+
+    fn next_sequence(&mut self, timestamp: u64) -> Result<FID> {
+        self.sequence += 1;
+        FID::new(timestamp, self.sequence, self.generator).map(|x| self.seal(x))
+    }
+
+    fn is_rate_limited(&self, timestamp: u64) -> bool {
+        match self.max_rate {
+            Some(max) => {
+                // ids are counted by their timestamp, not by clock behind it
+                let timestamp = self.clamp_monotonic(timestamp, self.timestamp_last);
+                // counter of previous second is reset by `count_rate`
+                let count = if timestamp / self.resolution.per_second() == self.rate_second {
+                    self.rate_count
+                } else {
+                    0
+                };
+                count >= max
+            }
+            None => false,
+        }
+    }
+
+    fn count_rate(&mut self, timestamp: u64) {
+        let second = timestamp / self.resolution.per_second();
+        if second != self.rate_second {
+            self.rate_second = second;
+            self.rate_count = 0;
+        }
+        self.rate_count += 1;
+    }
+
+    pub(crate) fn seal(&self, fid: FID) -> FID {
+        if self.parity_bit {
+            fid.with_parity()
+        } else if self.version != 0 {
+            fid.with_version(self.version).unwrap()
+        } else {
+            fid
+        }
+    }
+}
+
+/// Deterministic generators for downstream tests
+///
+/// **Test-only**, ids don't depend on system time and aren't unique across runs
+#[cfg(feature = "testing")]
+pub mod testing {
+    use id::FID;
+    use config as cfg;
+    use {Error, Result};
+
+    /// Generator with fixed timestamp, sequence increments per call
+    #[derive(Debug, Clone)]
+    pub struct FixedClockGenerator {
+        generator: u16,
+        timestamp: u64,
+        sequence: u32,
+    }
+
+    impl FixedClockGenerator {
+        /// Generate next id
+        ///
+        /// # Failures
+        /// `Error::SequenceOverflow` after `SEQUENCE_MAX + 1` ids
+        #[allow(clippy::should_implement_trait)]
+        pub fn next(&mut self) -> Result<FID> {
+            if self.sequence > u32::from(cfg::SEQUENCE_MAX) {
+                return Err(Error::SequenceOverflow(cfg::SEQUENCE_MAX));
+            }
+            let fid = FID::new(self.timestamp, self.sequence as u16, self.generator)?;
+            self.sequence += 1;
+            Ok(fid)
+        }
+    }
+
+    /// Create generator which always uses given timestamp, first id has zero sequence
+    ///
+    /// # Failures
+    /// `Error::TimestampOverflow`
+    /// `Error::GeneratorOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::testing::fixed_clock_generator;
+    /// let mut gen = fixed_clock_generator(0x12c, 0x204dc595637).unwrap();
+    /// assert_eq!(gen.next().unwrap().components(), (0x204dc595637, 0, 0x12c));
+    /// assert_eq!(gen.next().unwrap().components(), (0x204dc595637, 1, 0x12c));
+    /// ```
+    pub fn fixed_clock_generator(generator: u16, timestamp: u64) -> Result<FixedClockGenerator> {
+        FID::new(timestamp, 0, generator)?;
+        Ok(FixedClockGenerator {
+            generator,
+            timestamp,
+            sequence: 0,
+        })
+    }
+}
+
+/// Current time since offset in resolution units
+pub(crate) fn current_timestamp(timestamp_offset: i64, resolution: Resolution) -> Result<u64> {
+    let sys_time = SystemTime::now();
+    if timestamp_offset >= 0 {
+        // epoch before unix one may be unrepresentable, add offset to units instead
+        if sys_time < UNIX_EPOCH {
+            return Err(Error::SysTimeIsInPast);
+        }
+        let since_unix = sys_time
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| Error::SysTimeIsInPast)?;
+        let offset = (timestamp_offset as u64).saturating_mul(resolution.per_second());
+        return Ok(resolution.units(since_unix).saturating_add(offset));
+    }
+
+    let offset = UNIX_EPOCH
+        .checked_add(time::Duration::from_secs(timestamp_offset.unsigned_abs()))
+        .ok_or(Error::SysTimeIsInPast)?;
+    if sys_time < offset {
+        return Err(Error::SysTimeIsInPast);
+    }
+
+    let timestamp = sys_time
+        .duration_since(offset)
+        .map_err(|_| Error::SysTimeIsInPast)?;
+    Ok(resolution.units(timestamp))
+}
+
+/// Wait till system time moves to next timestamp of `resolution`
+///
+/// Spins `wait_spin` times, then sleeps at least `clock_resolution` ms per iteration
+pub(crate) fn wait_next_timestamp(
+    resolution: Resolution,
+    wait_spin: u32,
+    clock_resolution: u64,
+) -> Result<()> {
+    let start_time = SystemTime::now();
+    let mut spins = 0;
+    loop {
+        if let Ok(duration_since) = SystemTime::now().duration_since(start_time) {
+            let (elapsed, sleep) = match resolution {
+                Resolution::Seconds => (duration_since.as_secs() > 0, 10),
+                Resolution::Millis => (duration_since.subsec_millis() > 0, 1),
+                Resolution::Micros => (duration_since.subsec_micros() > 0, 0),
+            };
+            if elapsed {
+                return Ok(());
+            } else if spins < wait_spin {
+                spins += 1;
+                hint::spin_loop();
+            } else {
+                let sleep = cmp::max(sleep, clock_resolution);
+                thread::sleep(time::Duration::from_millis(sleep));
+            }
+        } else {
+            return Err(Error::SysTimeIsInPast);
+        }
+    }
+}
+
+fn random_sequence_start() -> u16 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish() as u16
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use config::test_constants::*;
 
     #[test]
     fn builder() {
@@ -232,9 +1423,14 @@ mod test {
             cfg::gbuilder_defaults::TIMESTAMP_OFFSET
         );
         assert_eq!(gen.0.wait_sequence, cfg::gbuilder_defaults::WAIT_SEQUENCE);
+        assert_eq!(gen.0.resolution, Resolution::default());
+        assert_eq!(gen.0.monotonic, cfg::gbuilder_defaults::MONOTONIC);
+        assert_eq!(gen.0.parity_bit, cfg::gbuilder_defaults::PARITY_BIT);
+        assert_eq!(gen.0.version, cfg::gbuilder_defaults::VERSION);
+        assert_eq!(FIDGeneratorBuilder::default().0.generator, 0);
         assert_eq!(
-            gen.0.timestamp_in_seconds,
-            cfg::gbuilder_defaults::TIMESTAMP_IN_SECONDS
+            FIDGeneratorBuilder::default().0.timestamp_offset,
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET
         );
         let mut gen = FIDGeneratorBuilder::new(0)
             .sequence(GENERATOR / 2)
@@ -251,40 +1447,413 @@ mod test {
         assert_eq!(gen.0.timestamp_last, TIMESTAMP / 2);
         assert_eq!(gen.0.timestamp_offset, -1800);
         assert_eq!(gen.0.wait_sequence, !cfg::gbuilder_defaults::WAIT_SEQUENCE);
+        assert_ne!(gen.0.resolution, Resolution::default());
+        let gen = gen.timestamp_in_microseconds();
+        assert_eq!(gen.0.resolution, Resolution::Micros);
+        let gen = gen.resolution(Resolution::Seconds);
+        assert_eq!(gen.0.resolution, Resolution::Seconds);
+    }
+
+    #[test]
+    fn epoch() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let offset = cfg::gbuilder_defaults::TIMESTAMP_OFFSET.unsigned_abs();
+        let epoch = time::UNIX_EPOCH + time::Duration::from_millis(offset * 1000 + 999);
+        let gen = FIDGeneratorBuilder::new(GENERATOR).epoch(epoch).unwrap();
+        assert_eq!(
+            gen.0.timestamp_offset,
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET
+        );
+        let epoch = time::UNIX_EPOCH - time::Duration::from_secs(1800);
+        let gen = FIDGeneratorBuilder::new(GENERATOR).epoch(epoch).unwrap();
+        assert_eq!(gen.0.timestamp_offset, 1800);
+        let future = offset + TIMESTAMP / 1000 + 1;
+        let epoch = time::UNIX_EPOCH + time::Duration::from_secs(future);
+        assert_eq!(
+            FIDGeneratorBuilder::new(GENERATOR)
+                .epoch(epoch)
+                .unwrap_err(),
+            Error::SysTimeIsInPast
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn derive_generator_id() {
+        assert_eq!(super::derive_generator_id(b""), 0x325);
+        assert_eq!(super::derive_generator_id(b"a"), 0x08c);
+        assert_eq!(
+            FIDGeneratorBuilder::from_seed(b"a").0.generator,
+            super::derive_generator_id(b"a")
+        );
+        let mut ids: Vec<_> = (0..64u8).map(|x| super::derive_generator_id(&[x])).collect();
+        ids.sort();
+        ids.dedup();
+        assert!(ids.len() > 48);
+    }
+
+    #[test]
+    fn random_generator() {
+        let gen = FIDGeneratorBuilder::random_generator(|| GENERATOR);
+        assert_eq!(gen.0.generator, GENERATOR);
+        let gen = FIDGeneratorBuilder::random_generator(|| 0xffff);
+        assert_eq!(gen.0.generator, cfg::GENERATOR_MAX);
+        let mut calls = 0;
+        FIDGeneratorBuilder::random_generator(|| {
+            calls += 1;
+            0
+        });
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn new() {
+        let lock_sys_time = SystemTime::lock(TIMESTAMP as i64);
+        FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR)).unwrap();
+        assert_eq!(
+            FIDGenerator::new(FIDGeneratorBuilder::new(1 << cfg::GENERATOR_LENGTH)).unwrap_err(),
+            Error::GeneratorOverflow(1 << cfg::GENERATOR_LENGTH)
+        );
+        assert_eq!(
+            FIDGenerator::new(
+                FIDGeneratorBuilder::new(GENERATOR).sequence(1 << cfg::SEQUENCE_LENGTH)
+            ).unwrap_err(),
+            Error::SequenceOverflow(1 << cfg::SEQUENCE_LENGTH)
+        );
+        assert_eq!(
+            FIDGenerator::new(
+                FIDGeneratorBuilder::new(GENERATOR).timestamp_last(1 << cfg::TIMESTAMP_LENGTH)
+            ).unwrap_err(),
+            Error::TimestampOverflow(1 << cfg::TIMESTAMP_LENGTH)
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn with_id() {
+        let lock_sys_time = SystemTime::lock(TIMESTAMP as i64);
+        FIDGenerator::with_id(GENERATOR).unwrap();
+        assert_eq!(
+            FIDGenerator::with_id(1 << cfg::GENERATOR_LENGTH).unwrap_err(),
+            Error::GeneratorOverflow(1 << cfg::GENERATOR_LENGTH)
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn from_env() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let vars = [
+            "FID_GENERATOR_ID",
+            "FID_TIMESTAMP_OFFSET",
+            "FID_RESOLUTION",
+            "FID_WAIT_SEQUENCE",
+        ];
+        for x in vars.iter() {
+            env::remove_var(x);
+        }
+        assert_eq!(
+            FIDGenerator::from_env().unwrap_err(),
+            Error::InvalidEnvVar("FID_GENERATOR_ID")
+        );
+        env::set_var("FID_GENERATOR_ID", GENERATOR.to_string());
+        let gen = FIDGenerator::from_env().unwrap();
+        assert_eq!(gen.generator_id(), GENERATOR);
+        assert_eq!(gen.offset(), cfg::gbuilder_defaults::TIMESTAMP_OFFSET);
+        assert_eq!(gen.resolution(), Resolution::default());
+        assert_eq!(gen.wait_sequence(), cfg::gbuilder_defaults::WAIT_SEQUENCE);
+
+        env::set_var("FID_TIMESTAMP_OFFSET", "-1483228000");
+        env::set_var("FID_RESOLUTION", "seconds");
+        env::set_var("FID_WAIT_SEQUENCE", "0");
+        let gen = FIDGenerator::from_env().unwrap();
+        assert_eq!(gen.offset(), -1483228000);
+        assert_eq!(gen.resolution(), Resolution::Seconds);
+        assert!(!gen.wait_sequence());
+
+        for (name, value) in [
+            ("FID_WAIT_SEQUENCE", "yes"),
+            ("FID_RESOLUTION", "hours"),
+            ("FID_TIMESTAMP_OFFSET", "abc"),
+            ("FID_GENERATOR_ID", "-1"),
+        ].iter()
+        {
+            env::set_var(name, value);
+            assert_eq!(FIDGenerator::from_env().unwrap_err(), Error::InvalidEnvVar(name));
+        }
+        for x in vars.iter() {
+            env::remove_var(x);
+        }
+        env::set_var("FID_GENERATOR_ID", "1024");
+        assert_eq!(FIDGenerator::from_env().unwrap_err(), Error::GeneratorOverflow(1024));
+        for x in vars.iter() {
+            env::remove_var(x);
+        }
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn validate() {
+        let lock_sys_time = SystemTime::lock(TIMESTAMP as i64);
+        let builder = FIDGeneratorBuilder::new(GENERATOR).sequence(1 << cfg::SEQUENCE_LENGTH);
+        assert_eq!(
+            builder.validate().unwrap_err(),
+            Error::SequenceOverflow(1 << cfg::SEQUENCE_LENGTH)
+        );
+        let builder = builder.sequence(SEQUENCE).timestamp_offset(-(TIMESTAMP as i64));
+        assert_eq!(
+            builder.validate().unwrap_err(),
+            Error::InvalidOffset(TIMESTAMP as i64 - TIMESTAMP as i64 * 1000, cfg::TIMESTAMP_MAX)
+        );
+        let builder = builder.timestamp_offset(-1800);
+        builder.validate().unwrap();
+        assert_eq!(builder.build().unwrap().sequence, SEQUENCE);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn invalid_offset() {
+        let max = (1 << cfg::TIMESTAMP_LENGTH) - 1;
+        let lock_sys_time = SystemTime::lock(1800 * 1000);
+        assert_eq!(
+            FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).timestamp_offset(-3600))
+                .unwrap_err(),
+            Error::InvalidOffset(-1800 * 1000, max)
+        );
+        assert_eq!(
+            FIDGenerator::new(
+                FIDGeneratorBuilder::new(GENERATOR).timestamp_offset(max as i64 / 1000)
+            ).unwrap_err(),
+            Error::InvalidOffset((1800 + max as i64 / 1000) * 1000, max)
+        );
+        assert_eq!(
+            FIDGenerator::new(
+                FIDGeneratorBuilder::new(GENERATOR)
+                    .timestamp_in_seconds()
+                    .timestamp_offset(max as i64)
+            ).unwrap_err(),
+            Error::InvalidOffset(1800 + max as i64, max)
+        );
+        FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).timestamp_offset(-1800)).unwrap();
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn positive_offset() {
+        let lock_sys_time = SystemTime::lock(1800 * 1000 + 5);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR).timestamp_offset(0).build().unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), 1800 * 1000 + 5);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR).timestamp_offset(3600).build().unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), (1800 + 3600) * 1000 + 5);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .timestamp_in_seconds()
+            .timestamp_offset(i64::MAX)
+            .on_timestamp_overflow(TimestampOverflowPolicy::Saturate)
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), cfg::TIMESTAMP_MAX);
+        assert_eq!(current_timestamp(i64::MAX, Resolution::Millis).unwrap(), u64::MAX);
+        assert_eq!(
+            current_timestamp(i64::MIN, Resolution::Millis).unwrap_err(),
+            Error::SysTimeIsInPast
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn timestamp_in_microseconds() {
+        let mut lock_sys_time = SystemTime::lock(1800 * 1000 + 5);
+        let builder = FIDGeneratorBuilder::new(GENERATOR).timestamp_in_microseconds();
+        assert_eq!(
+            FIDGenerator::new(builder.clone()).unwrap_err(),
+            Error::InvalidOffset(
+                1_800_005_000 + cfg::gbuilder_defaults::TIMESTAMP_OFFSET * 1_000_000,
+                cfg::TIMESTAMP_MAX
+            )
+        );
+        let mut gen = FIDGenerator::new(builder.timestamp_offset(-1800)).unwrap();
+        assert!(!gen.is_seconds());
+        assert_eq!(gen.resolution(), Resolution::Micros);
+        let fid = gen.next().unwrap();
+        assert_eq!(fid.components(), (5000, 0, GENERATOR));
+        assert_eq!(gen.next().unwrap().components(), (5000, 1, GENERATOR));
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().components(), (6000, 0, GENERATOR));
+        assert_eq!(gen.age_of(&fid).unwrap(), time::Duration::from_millis(1));
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn set_generator() {
+        let lock_sys_time = SystemTime::lock(TIMESTAMP as i64);
+        let mut gen = FIDGeneratorBuilder::new(0)
+            .sequence(SEQUENCE)
+            .timestamp_last(TIMESTAMP)
+            .build()
+            .unwrap();
+        gen.set_generator(GENERATOR).unwrap();
+        assert_eq!(gen.generator, GENERATOR);
+        assert_eq!(gen.sequence, 0);
+        assert_eq!(gen.timestamp_last, TIMESTAMP);
+        assert_eq!(
+            gen.set_generator(1 << cfg::GENERATOR_LENGTH).unwrap_err(),
+            Error::GeneratorOverflow(1 << cfg::GENERATOR_LENGTH)
+        );
+        assert_eq!(gen.generator, GENERATOR);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn reset() {
+        let lock_sys_time = SystemTime::lock(TIMESTAMP as i64);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .timestamp_offset(-1800)
+            .build()
+            .unwrap();
+        gen.reset_to(TIMESTAMP, SEQUENCE).unwrap();
+        assert_eq!(gen.timestamp_last, TIMESTAMP);
+        assert_eq!(gen.sequence, SEQUENCE);
+        assert_eq!(
+            gen.reset_to(1 << cfg::TIMESTAMP_LENGTH, 0).unwrap_err(),
+            Error::TimestampOverflow(1 << cfg::TIMESTAMP_LENGTH)
+        );
+        assert_eq!(
+            gen.reset_to(0, 1 << cfg::SEQUENCE_LENGTH).unwrap_err(),
+            Error::SequenceOverflow(1 << cfg::SEQUENCE_LENGTH)
+        );
+        assert_eq!(gen.timestamp_last, TIMESTAMP);
+        gen.reset();
+        assert_eq!(gen.timestamp_last, 0);
+        assert_eq!(gen.sequence, 0);
+        assert_eq!(gen.generator, GENERATOR);
+        assert_eq!(gen.timestamp_offset, -1800);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn age_of() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR).build().unwrap();
+        let fid = gen.next().unwrap();
+        assert_eq!(gen.age_of(&fid).unwrap(), time::Duration::from_secs(0));
+        lock_sys_time.add(1500);
+        assert_eq!(gen.age_of(&fid).unwrap(), time::Duration::from_millis(1500));
+        let future = FID::new(TIMESTAMP + 1501, 0, GENERATOR).unwrap();
+        assert_eq!(gen.age_of(&future).unwrap_err(), Error::SysTimeIsInPast);
+
+        let gen = FIDGeneratorBuilder::new(GENERATOR)
+            .timestamp_in_seconds()
+            .build()
+            .unwrap();
+        let fid = FID::new((TIMESTAMP + 1500) / 1000 - 60, 0, GENERATOR).unwrap();
+        assert_eq!(gen.age_of(&fid).unwrap(), time::Duration::from_secs(60));
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn collides_with() {
+        let lock_sys_time = SystemTime::lock(TIMESTAMP as i64);
+        let gen = FIDGeneratorBuilder::new(GENERATOR).build().unwrap();
+        let same = FIDGeneratorBuilder::new(GENERATOR)
+            .sequence(SEQUENCE)
+            .wait_sequence(false)
+            .build()
+            .unwrap();
+        assert!(gen.collides_with(&same));
+        assert!(same.collides_with(&gen));
+        for other in [
+            FIDGeneratorBuilder::new(GENERATOR + 1),
+            FIDGeneratorBuilder::new(GENERATOR).timestamp_offset(-1800),
+            FIDGeneratorBuilder::new(GENERATOR).timestamp_in_seconds(),
+        ].iter()
+        {
+            assert!(!gen.collides_with(&other.clone().build().unwrap()));
+        }
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_sequence(false)
+            .build()
+            .unwrap();
+        gen.next().unwrap();
+        let fid = gen.next().unwrap();
+        let state = gen.snapshot();
         assert_eq!(
-            gen.0.timestamp_in_seconds,
-            !cfg::gbuilder_defaults::TIMESTAMP_IN_SECONDS
+            state,
+            GeneratorState {
+                generator: GENERATOR,
+                timestamp_last: TIMESTAMP,
+                sequence: 1,
+                timestamp_offset: cfg::gbuilder_defaults::TIMESTAMP_OFFSET,
+                resolution: Resolution::default(),
+            }
         );
+        let mut gen = FIDGeneratorBuilder::from_state(state).build().unwrap();
+        assert_eq!(gen.snapshot(), state);
+        let next = gen.next().unwrap();
+        assert!(next > fid);
+        assert_eq!(next.sequence(), 2);
+        lock_sys_time.add(-1);
+        let mut gen = FIDGeneratorBuilder::from_state(state).build().unwrap();
+        assert_eq!(gen.next().unwrap_err(), Error::SysTimeIsInPast);
     }
 
     #[test]
-    fn new() {
-        FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR)).unwrap();
-        assert_eq!(
-            FIDGenerator::new(FIDGeneratorBuilder::new(1 << cfg::GENERATOR_LENGTH)).unwrap_err(),
-            Error::GeneratorOverflow(1 << cfg::GENERATOR_LENGTH)
+    fn status() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
         );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_sequence(false)
+            .build()
+            .unwrap();
+        gen.next().unwrap();
         assert_eq!(
-            FIDGenerator::new(
-                FIDGeneratorBuilder::new(GENERATOR).sequence(1 << cfg::SEQUENCE_LENGTH)
-            ).unwrap_err(),
-            Error::SequenceOverflow(1 << cfg::SEQUENCE_LENGTH)
+            gen.status(),
+            GeneratorStatus {
+                generator: GENERATOR,
+                resolution: Resolution::default(),
+                timestamp_offset: cfg::gbuilder_defaults::TIMESTAMP_OFFSET,
+                timestamp_last: TIMESTAMP,
+                sequence: 0,
+                wait_sequence: false,
+            }
         );
+        assert_eq!(gen.generator_id(), GENERATOR);
+        assert_eq!(gen.offset(), cfg::gbuilder_defaults::TIMESTAMP_OFFSET);
+        assert_eq!(gen.is_seconds(), cfg::gbuilder_defaults::TIMESTAMP_IN_SECONDS);
+        assert_eq!(gen.timestamp_offset(), cfg::gbuilder_defaults::TIMESTAMP_OFFSET);
+        assert_eq!(gen.timestamp_last(), TIMESTAMP);
+        assert_eq!(gen.next().unwrap().sequence(), gen.current_sequence());
+        assert_eq!(gen.current_sequence(), 1);
+        assert!(!gen.wait_sequence());
         assert_eq!(
-            FIDGenerator::new(
-                FIDGeneratorBuilder::new(GENERATOR).timestamp_last(1 << cfg::TIMESTAMP_LENGTH)
-            ).unwrap_err(),
-            Error::TimestampOverflow(1 << cfg::TIMESTAMP_LENGTH)
+            gen.timestamp_in_seconds(),
+            cfg::gbuilder_defaults::TIMESTAMP_IN_SECONDS
         );
+        SystemTime::unlock(lock_sys_time);
     }
 
     #[test]
     #[allow(non_snake_case)]
     fn SysTimeIsInPast() {
         let mut lock_sys_time =
-            SystemTime::lock((cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() - 1) * 1000);
+            SystemTime::lock(cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000);
         let mut gen =
             FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        lock_sys_time.add(-1000);
         assert_eq!(gen.next().unwrap_err(), Error::SysTimeIsInPast);
         lock_sys_time.add(1001);
         assert_eq!(gen.next().unwrap().timestamp(), 1);
@@ -294,14 +1863,261 @@ mod test {
         assert_eq!(gen.next().unwrap_err(), Error::SysTimeIsInPast);
     }
 
+    #[test]
+    fn monotonic() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGenerator::new(
+            FIDGeneratorBuilder::new(GENERATOR)
+                .wait_sequence(false)
+                .monotonic(true),
+        ).unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP);
+        lock_sys_time.add(-10);
+        for i in 1..(1 << cfg::SEQUENCE_LENGTH) {
+            let fid = gen.next().unwrap();
+            assert_eq!(fid.timestamp(), TIMESTAMP);
+            assert_eq!(fid.sequence(), i);
+        }
+        assert_eq!(
+            gen.next().unwrap_err(),
+            Error::SequenceOverflow((1 << cfg::SEQUENCE_LENGTH) - 1)
+        );
+        lock_sys_time.add(11);
+        let fid = gen.next().unwrap();
+        assert_eq!(fid.timestamp(), TIMESTAMP + 1);
+        assert_eq!(fid.sequence(), 0);
+    }
+
+    #[test]
+    fn parity_bit() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).parity_bit(true))
+            .unwrap();
+        for i in 0..16 {
+            let fid = gen.next().unwrap();
+            assert!(fid.verify_parity());
+            assert_eq!(fid.components(), (TIMESTAMP, i, GENERATOR));
+        }
+        assert!(gen.next_at(TIMESTAMP + 1).unwrap().verify_parity());
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn next_generator_high() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut low = FIDGeneratorBuilder::new(GENERATOR)
+            .parity_bit(true)
+            .build()
+            .unwrap();
+        let mut high = FIDGeneratorBuilder::new(GENERATOR + 1).build().unwrap();
+        let last = high.next_at(TIMESTAMP - 1).unwrap().to_generator_high();
+        let (lo, hi) = GeneratorHighFID::range_for_generator(GENERATOR).unwrap();
+        for i in 0..16 {
+            let fid = low.next_generator_high().unwrap();
+            assert!(fid.verify_parity());
+            assert_eq!(fid.components(), (TIMESTAMP, i, GENERATOR));
+            let raw = u64::from(fid) & !cfg::PARITY_MASK;
+            assert!(u64::from(lo.clone()) <= raw && raw <= u64::from(hi.clone()));
+            assert!(raw < u64::from(last.clone()));
+        }
+        assert_eq!(last.components(), (TIMESTAMP - 1, 0, GENERATOR + 1));
+        assert_eq!(high.next_generator_high().unwrap().timestamp(), TIMESTAMP);
+        assert_eq!(high.age_of(&last.to_fid()).unwrap(), time::Duration::from_millis(1));
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn version() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR).version(1).build().unwrap();
+        for i in 0..4 {
+            let fid = gen.next().unwrap();
+            assert_eq!(fid.version(), 1);
+            assert_eq!(fid.components(), (TIMESTAMP, i, GENERATOR));
+        }
+        assert_eq!(gen.peek_next().unwrap().version(), 1);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR).build().unwrap();
+        assert_eq!(gen.next().unwrap().version(), 0);
+        assert_eq!(
+            FIDGeneratorBuilder::new(GENERATOR).version(2).build().unwrap_err(),
+            Error::InvalidVersion(2)
+        );
+        assert_eq!(
+            FIDGeneratorBuilder::new(GENERATOR)
+                .version(1)
+                .parity_bit(true)
+                .build()
+                .unwrap_err(),
+            Error::ReservedBitConflict
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn max_future_skew() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .max_future_skew(time::Duration::from_secs(60))
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP);
+        lock_sys_time.add(60 * 1000);
+        assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP + 60 * 1000);
+        lock_sys_time.add(60 * 1000 + 1);
+        assert_eq!(gen.peek_next().unwrap_err(), Error::SysTimeTooFarAhead);
+        assert_eq!(gen.next().unwrap_err(), Error::SysTimeTooFarAhead);
+        let far = cfg::TIMESTAMP_MAX - TIMESTAMP;
+        lock_sys_time.add(far as i64);
+        assert_eq!(gen.next().unwrap_err(), Error::SysTimeTooFarAhead);
+        gen.reset();
+        let expected = cfg::TIMESTAMP_MAX + 120_001;
+        assert_eq!(gen.next().unwrap_err(), Error::TimestampOverflow(expected));
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn max_future_skew_idle() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .max_future_skew(time::Duration::from_millis(5))
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP);
+        // pause longer than skew, system time advances alike monotonic clock
+        thread::sleep(time::Duration::from_millis(20));
+        lock_sys_time.add(20);
+        assert_eq!(gen.peek_next().unwrap().timestamp(), TIMESTAMP + 20);
+        assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP + 20);
+        lock_sys_time.add(1000);
+        assert_eq!(gen.next().unwrap_err(), Error::SysTimeTooFarAhead);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn random_sequence_start() {
+        fn source() -> u16 {
+            (1 << cfg::SEQUENCE_LENGTH) + SEQUENCE
+        }
+
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_sequence(false)
+            .sequence_source(source)
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().sequence(), SEQUENCE);
+        assert_eq!(gen.next().unwrap().sequence(), SEQUENCE + 1);
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().sequence(), SEQUENCE);
+        for _ in SEQUENCE + 1..(1 << cfg::SEQUENCE_LENGTH) {
+            gen.next().unwrap();
+        }
+        assert_eq!(
+            gen.next().unwrap_err(),
+            Error::SequenceOverflow((1 << cfg::SEQUENCE_LENGTH) - 1)
+        );
+
+        // stateful source is shared by clones
+        let mut start = 0;
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .sequence_source(move || {
+                start += 2;
+                start
+            })
+            .build()
+            .unwrap();
+        let mut other = gen.clone();
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().sequence(), 2);
+        assert_eq!(other.next().unwrap().sequence(), 4);
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().sequence(), 6);
+
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .random_sequence_start(true)
+            .build()
+            .unwrap();
+        let starts: Vec<_> = (0..16)
+            .map(|_| {
+                lock_sys_time.add(1);
+                gen.next().unwrap().sequence()
+            })
+            .collect();
+        assert!(starts.iter().any(|x| *x != 0));
+
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .random_sequence_start(true)
+            .random_sequence_start(false)
+            .build()
+            .unwrap();
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().sequence(), 0);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn on_timestamp_overflow() {
+        let max = (1 << cfg::TIMESTAMP_LENGTH) - 1;
+        let mut lock_sys_time =
+            SystemTime::lock(cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + max as i64);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .on_timestamp_overflow(TimestampOverflowPolicy::Saturate)
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), max);
+        lock_sys_time.add(5);
+        for i in 1..(1 << cfg::SEQUENCE_LENGTH) {
+            let fid = gen.next().unwrap();
+            assert_eq!(fid.timestamp(), max);
+            assert_eq!(fid.sequence(), i);
+        }
+        assert_eq!(
+            gen.next().unwrap_err(),
+            Error::SequenceOverflow((1 << cfg::SEQUENCE_LENGTH) - 1)
+        );
+        assert_eq!(
+            FIDGeneratorBuilder::new(GENERATOR).build().unwrap_err(),
+            Error::InvalidOffset(max as i64 + 5, max)
+        );
+
+        lock_sys_time.add(-5);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .on_timestamp_overflow(TimestampOverflowPolicy::Wrap)
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), max);
+        lock_sys_time.add(1);
+        let fid = gen.next().unwrap();
+        assert_eq!(fid.timestamp(), 0);
+        assert_eq!(fid.sequence(), 0);
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().timestamp(), 1);
+        SystemTime::unlock(lock_sys_time);
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn TimestampOverflow() {
-        let lock_sys_time = SystemTime::lock(
-            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + (1 << cfg::TIMESTAMP_LENGTH),
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + (1 << cfg::TIMESTAMP_LENGTH) - 1,
         );
         let mut gen =
             FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        lock_sys_time.add(1);
         assert_eq!(
             gen.next().unwrap_err(),
             Error::TimestampOverflow(1 << cfg::TIMESTAMP_LENGTH)
@@ -361,6 +2177,341 @@ mod test {
         SystemTime::unlock(lock_sys_time);
     }
 
+    #[test]
+    fn wait_count() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR)).unwrap();
+        gen.next().unwrap();
+        gen.sequence = (1 << cfg::SEQUENCE_LENGTH) - 1;
+        // stub clock never ticks on its own
+        let handle = thread::spawn(move || {
+            thread::sleep(time::Duration::from_millis(50));
+            lock_sys_time.add(1);
+            lock_sys_time
+        });
+        let (fid, waited) = gen.next_timed().unwrap();
+        assert_eq!(fid.timestamp(), TIMESTAMP + 1);
+        assert!(waited >= time::Duration::from_millis(40));
+        assert_eq!(gen.wait_count(), 1);
+        assert_eq!(gen.next_timed().unwrap().1, time::Duration::from_secs(0));
+        gen.reset_stats();
+        assert_eq!(gen.wait_count(), 0);
+        SystemTime::unlock(handle.join().unwrap());
+    }
+
+    #[test]
+    fn wait_spin() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        assert_eq!(
+            FIDGeneratorBuilder::new(GENERATOR).wait_spin(u32::MAX).0.wait_spin,
+            WAIT_SPIN_MAX
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_spin(1000)
+            .build()
+            .unwrap();
+        gen.next().unwrap();
+        gen.sequence = (1 << cfg::SEQUENCE_LENGTH) - 1;
+        let handle = thread::spawn(move || {
+            thread::sleep(time::Duration::from_millis(10));
+            lock_sys_time.add(1);
+            lock_sys_time
+        });
+        let fid = gen.next().unwrap();
+        assert_eq!(fid.timestamp(), TIMESTAMP + 1);
+        assert_eq!(fid.sequence(), 0);
+        SystemTime::unlock(handle.join().unwrap());
+    }
+
+    #[test]
+    fn next_at() {
+        let lock_sys_time = SystemTime::lock(TIMESTAMP as i64);
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .timestamp_offset(0)
+            .build()
+            .unwrap();
+        assert_eq!(
+            gen.next_at(TIMESTAMP).unwrap().components(),
+            (TIMESTAMP, 0, GENERATOR)
+        );
+        for i in 1..(1 << cfg::SEQUENCE_LENGTH) {
+            assert_eq!(gen.next_at(TIMESTAMP).unwrap().sequence(), i);
+        }
+        assert_eq!(
+            gen.next_at(TIMESTAMP).unwrap_err(),
+            Error::SequenceOverflow(cfg::SEQUENCE_MAX)
+        );
+        assert_eq!(gen.next_at(TIMESTAMP - 1).unwrap_err(), Error::SysTimeIsInPast);
+        assert_eq!(
+            gen.next_at(cfg::TIMESTAMP_MAX + 1).unwrap_err(),
+            Error::TimestampOverflow(cfg::TIMESTAMP_MAX + 1)
+        );
+        assert_eq!(
+            gen.next_at(TIMESTAMP + 10).unwrap().components(),
+            (TIMESTAMP + 10, 0, GENERATOR)
+        );
+
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .timestamp_offset(0)
+            .monotonic(true)
+            .build()
+            .unwrap();
+        gen.next_at(TIMESTAMP).unwrap();
+        assert_eq!(
+            gen.next_at(TIMESTAMP - 1).unwrap().components(),
+            (TIMESTAMP, 1, GENERATOR)
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn sequence_max() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen =
+            FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        gen.reset_to(TIMESTAMP, cfg::SEQUENCE_MAX - 1).unwrap();
+        assert_eq!(gen.next().unwrap().sequence(), cfg::SEQUENCE_MAX);
+        assert_eq!(
+            gen.next().unwrap_err(),
+            Error::SequenceOverflow(cfg::SEQUENCE_MAX)
+        );
+        gen.reset_to(TIMESTAMP, cfg::SEQUENCE_MAX).unwrap();
+        assert_eq!(
+            gen.next().unwrap_err(),
+            Error::SequenceOverflow(cfg::SEQUENCE_MAX)
+        );
+        assert_eq!(
+            gen.reset_to(TIMESTAMP, cfg::SEQUENCE_MAX + 1).unwrap_err(),
+            Error::SequenceOverflow(cfg::SEQUENCE_MAX + 1)
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn assume_clock_resolution_ms() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .assume_clock_resolution_ms(15)
+            .build()
+            .unwrap();
+        assert_eq!(gen.clock_resolution, 15);
+        gen.next().unwrap();
+        gen.sequence = cfg::SEQUENCE_MAX;
+        let handle = thread::spawn(move || {
+            thread::sleep(time::Duration::from_millis(5));
+            lock_sys_time.add(15);
+            lock_sys_time
+        });
+        let (fid, waited) = gen.next_timed().unwrap();
+        assert_eq!(fid.timestamp(), TIMESTAMP + 15);
+        assert!(waited >= time::Duration::from_millis(15));
+        SystemTime::unlock(handle.join().unwrap());
+    }
+
+    #[test]
+    fn peek_next() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false))
+            .unwrap();
+        assert_eq!(gen.remaining_sequence(), cfg::SEQUENCE_MAX);
+        for _ in 0..cfg::SEQUENCE_MAX {
+            let peeked = gen.peek_next().unwrap();
+            assert_eq!(gen.next().unwrap(), peeked);
+        }
+        assert_eq!(gen.remaining_sequence(), 1);
+        assert_eq!(gen.next().unwrap().sequence(), cfg::SEQUENCE_MAX);
+        assert_eq!(gen.remaining_sequence(), 0);
+        assert_eq!(
+            gen.peek_next().unwrap_err(),
+            Error::SequenceOverflow(cfg::SEQUENCE_MAX)
+        );
+        lock_sys_time.add(-1);
+        assert_eq!(gen.peek_next().unwrap_err(), Error::SysTimeIsInPast);
+        lock_sys_time.add(2);
+        let peeked = gen.peek_next().unwrap();
+        assert_eq!(peeked.components(), (TIMESTAMP + 1, 0, GENERATOR));
+        assert_eq!(gen.peek_next().unwrap(), peeked);
+        assert_eq!(gen.next().unwrap(), peeked);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn max_rate_per_second() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGenerator::new(
+            FIDGeneratorBuilder::new(GENERATOR)
+                .wait_sequence(false)
+                .max_rate_per_second(3),
+        ).unwrap();
+        for _ in 0..3 {
+            gen.next().unwrap();
+        }
+        assert_eq!(gen.next().unwrap_err(), Error::RateLimited);
+        assert_eq!(gen.peek_next().unwrap_err(), Error::RateLimited);
+        let left = 1000 - TIMESTAMP % 1000;
+        lock_sys_time.add(left as i64 - 1);
+        assert_eq!(gen.next().unwrap_err(), Error::RateLimited);
+        assert!(gen.next_at(TIMESTAMP + left - 1).is_ok());
+        lock_sys_time.add(1);
+        assert_eq!(gen.peek_next().unwrap().timestamp(), TIMESTAMP + left);
+        for _ in 0..3 {
+            assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP + left);
+        }
+        assert_eq!(gen.next().unwrap_err(), Error::RateLimited);
+
+        // clock behind last id second is limited by id timestamp
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_sequence(false)
+            .monotonic(true)
+            .max_rate_per_second(3)
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            gen.next().unwrap();
+        }
+        lock_sys_time.add(-2000);
+        assert_eq!(gen.peek_next().unwrap_err(), Error::RateLimited);
+        assert_eq!(gen.next().unwrap_err(), Error::RateLimited);
+        lock_sys_time.add(2000 + 1000);
+        assert_eq!(gen.next().unwrap().sequence(), 0);
+        assert_eq!(
+            FIDGeneratorBuilder::new(GENERATOR)
+                .max_rate_per_second(0)
+                .build()
+                .unwrap_err(),
+            Error::InvalidRateLimit
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    #[cfg(feature = "testing")]
+    fn fixed_clock_generator() {
+        let mut gen = testing::fixed_clock_generator(GENERATOR, TIMESTAMP).unwrap();
+        for i in 0..=cfg::SEQUENCE_MAX {
+            assert_eq!(gen.next().unwrap().components(), (TIMESTAMP, i, GENERATOR));
+        }
+        assert_eq!(gen.next().unwrap_err(), Error::SequenceOverflow(cfg::SEQUENCE_MAX));
+        assert_eq!(
+            testing::fixed_clock_generator(cfg::GENERATOR_MAX + 1, TIMESTAMP).unwrap_err(),
+            Error::GeneratorOverflow(cfg::GENERATOR_MAX + 1)
+        );
+        assert_eq!(
+            testing::fixed_clock_generator(GENERATOR, cfg::TIMESTAMP_MAX + 1).unwrap_err(),
+            Error::TimestampOverflow(cfg::TIMESTAMP_MAX + 1)
+        );
+    }
+
+    #[test]
+    fn overflow_policy() {
+        struct Borrow(u32);
+        impl OverflowPolicy for Borrow {
+            fn on_overflow(&mut self, gen: &mut FIDGenerator) -> Result<FID> {
+                self.0 += 1;
+                assert!(gen.overflow_policy.is_none());
+                let timestamp = gen.timestamp_last + 1;
+                gen.next_at(timestamp)
+            }
+        }
+
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_sequence(false)
+            .overflow_policy(Borrow(0))
+            .build()
+            .unwrap();
+        gen.next().unwrap();
+        gen.sequence = cfg::SEQUENCE_MAX;
+        let fid = gen.next().unwrap();
+        assert_eq!(fid.components(), (TIMESTAMP + 1, 0, GENERATOR));
+        assert!(gen.overflow_policy.is_some());
+
+        struct Slow;
+        impl OverflowPolicy for Slow {
+            fn on_overflow(&mut self, gen: &mut FIDGenerator) -> Result<FID> {
+                thread::sleep(time::Duration::from_millis(5));
+                Err(Error::SequenceOverflow(gen.sequence))
+            }
+        }
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .overflow_policy(Slow)
+            .build()
+            .unwrap();
+        gen.next().unwrap();
+        gen.sequence = cfg::SEQUENCE_MAX;
+        let mut waited = time::Duration::from_secs(0);
+        assert!(gen.next_waited(Some(&mut waited)).is_err());
+        assert!(waited >= time::Duration::from_millis(5));
+
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .overflow_policy(ErrorPolicy)
+            .build()
+            .unwrap();
+        gen.next().unwrap();
+        gen.sequence = cfg::SEQUENCE_MAX;
+        assert_eq!(
+            gen.next().unwrap_err(),
+            Error::SequenceOverflow(cfg::SEQUENCE_MAX)
+        );
+
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_sequence(false)
+            .overflow_policy(WaitPolicy)
+            .build()
+            .unwrap();
+        gen.next().unwrap();
+        gen.sequence = cfg::SEQUENCE_MAX;
+        let handle = thread::spawn(move || {
+            thread::sleep(time::Duration::from_millis(10));
+            lock_sys_time.add(1);
+            lock_sys_time
+        });
+        let fid = gen.next().unwrap();
+        assert_eq!(fid.components(), (TIMESTAMP + 1, 0, GENERATOR));
+        assert_eq!(gen.wait_count(), 1);
+        assert!(gen.overflow_policy.is_some());
+        SystemTime::unlock(handle.join().unwrap());
+    }
+
+    #[test]
+    fn wait_policy_clock_behind() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FIDGeneratorBuilder::new(GENERATOR)
+            .wait_sequence(false)
+            .monotonic(true)
+            .max_rate_per_second(1 << 20)
+            .overflow_policy(WaitPolicy)
+            .build()
+            .unwrap();
+        gen.next().unwrap();
+        gen.sequence = cfg::SEQUENCE_MAX;
+        lock_sys_time.add(-1);
+        // steps ahead of clock instead of waiting until it catches up
+        let (fid, waited) = gen.next_timed().unwrap();
+        assert_eq!(fid.components(), (TIMESTAMP + 1, 0, GENERATOR));
+        assert_eq!(waited, time::Duration::from_secs(0));
+        assert_eq!(gen.wait_count(), 0);
+        assert_eq!(gen.next().unwrap().components(), (TIMESTAMP + 1, 1, GENERATOR));
+        assert_eq!(gen.rate_count, 3);
+        SystemTime::unlock(lock_sys_time);
+    }
+
     #[test]
     fn next() {
         let lock_sys_time = SystemTime::lock(
@@ -376,4 +2527,23 @@ mod test {
         }
         SystemTime::unlock(lock_sys_time);
     }
+
+    #[test]
+    fn next_u64() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen =
+            FIDGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        assert_eq!(
+            FID::from(gen.next_u64().unwrap()).components(),
+            (TIMESTAMP, 0, GENERATOR)
+        );
+        assert_eq!(FID::from(gen.next_u64().unwrap()).sequence(), 1);
+        assert_eq!(
+            gen.next_u64().unwrap(),
+            u64::from(FID::new(TIMESTAMP, 2, GENERATOR).unwrap())
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
 }
@@ -0,0 +1,272 @@
+//! Extended 128-bit flower identificator generator
+use std::cmp;
+use id128::FID128;
+use config as cfg;
+use generator::{current_timestamp, wait_next_timestamp, Resolution, WAIT_SPIN_MAX};
+
+use {Error, Result};
+
+/// Extended flower identificator generator
+#[derive(Debug, Clone)]
+pub struct FID128Generator {
+    generator: u32,
+    timestamp_offset: i64,
+    timestamp_last: u64,
+    sequence: u32,
+    wait_sequence: bool,
+    resolution: Resolution,
+    wait_spin: u32,
+    clock_resolution: u64,
+}
+
+/// Extended generator configuration builder
+///
+/// alike `FIDGeneratorBuilder`
+#[derive(Debug, Clone)]
+pub struct FID128GeneratorBuilder(FID128Generator);
+
+impl FID128GeneratorBuilder {
+    /// Create builder with default values
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator128::FID128GeneratorBuilder;
+    /// let mut gen = FID128GeneratorBuilder::new(0x12c)
+    ///     .timestamp_offset(-1483228800)
+    ///     .timestamp_in_milliseconds()
+    ///     .wait_sequence(true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(gen.next().unwrap().generator(), 0x12c);
+    /// ```
+    pub fn new(generator: u32) -> FID128GeneratorBuilder {
+        FID128GeneratorBuilder(FID128Generator {
+            generator,
+            timestamp_offset: cfg::gbuilder_defaults::TIMESTAMP_OFFSET,
+            timestamp_last: 0,
+            sequence: 0,
+            wait_sequence: cfg::gbuilder_defaults::WAIT_SEQUENCE,
+            resolution: Resolution::default(),
+            wait_spin: cfg::gbuilder_defaults::WAIT_SPIN,
+            clock_resolution: cfg::gbuilder_defaults::CLOCK_RESOLUTION_MS,
+        })
+    }
+
+    /// Set timestamp last timestamp
+    pub fn timestamp_last(mut self, val: u64) -> FID128GeneratorBuilder {
+        self.0.timestamp_last = val;
+        self
+    }
+
+    /// Set timestamp resolution to seconds
+    pub fn timestamp_in_seconds(self) -> FID128GeneratorBuilder {
+        self.resolution(Resolution::Seconds)
+    }
+
+    /// Set timestamp resolution to milliseconds
+    pub fn timestamp_in_milliseconds(self) -> FID128GeneratorBuilder {
+        self.resolution(Resolution::Millis)
+    }
+
+    /// Set timestamp resolution
+    ///
+    /// Default 64 bits of timestamp fit any resolution
+    pub fn resolution(mut self, val: Resolution) -> FID128GeneratorBuilder {
+        self.0.resolution = val;
+        self
+    }
+
+    /// Set sequence
+    pub fn sequence(mut self, val: u32) -> FID128GeneratorBuilder {
+        self.0.sequence = val;
+        self
+    }
+
+    /// Set timestamp offset (in seconds)
+    pub fn timestamp_offset(mut self, val: i64) -> FID128GeneratorBuilder {
+        self.0.timestamp_offset = val;
+        self
+    }
+
+    /// Set wait sequence
+    ///
+    /// If wait_sequence is true and sequence overflowed, generator wait till next timetamp has been generated
+    pub fn wait_sequence(mut self, val: bool) -> FID128GeneratorBuilder {
+        self.0.wait_sequence = val;
+        self
+    }
+
+    /// Set busy-spin count of sequence overflow wait
+    ///
+    /// alike `FIDGeneratorBuilder::wait_spin`
+    pub fn wait_spin(mut self, spins: u32) -> FID128GeneratorBuilder {
+        self.0.wait_spin = cmp::min(spins, WAIT_SPIN_MAX);
+        self
+    }
+
+    /// Set assumed system clock resolution (in milliseconds)
+    ///
+    /// alike `FIDGeneratorBuilder::assume_clock_resolution_ms`
+    pub fn assume_clock_resolution_ms(mut self, val: u64) -> FID128GeneratorBuilder {
+        self.0.clock_resolution = val;
+        self
+    }
+
+    /// Build `FID128Generator`
+    ///
+    /// # Failures
+    /// `Error::Generator128Overflow`
+    /// `Error::Sequence128Overflow`
+    /// `Error::TimestampOverflow` last or current timestamp doesn't fit `TIMESTAMP128_LENGTH`
+    /// `Error::SysTimeIsInPast` offset is in the future
+    // overflow checks are always false for default full-width layout
+    #[allow(clippy::absurd_extreme_comparisons)]
+    pub fn build(self) -> Result<FID128Generator> {
+        if self.0.generator > cfg::GENERATOR128_MAX {
+            return Err(Error::Generator128Overflow(self.0.generator));
+        } else if self.0.sequence > cfg::SEQUENCE128_MAX {
+            return Err(Error::Sequence128Overflow(self.0.sequence));
+        } else if self.0.timestamp_last > cfg::TIMESTAMP128_MAX {
+            return Err(Error::TimestampOverflow(self.0.timestamp_last));
+        }
+        self.0.new_timestamp()?;
+        Ok(self.0)
+    }
+}
+
+impl FID128Generator {
+    /// Generate next id
+    ///
+    /// # Failures
+    /// `Error::SysTimeIsInPast`
+    /// `Error::Sequence128Overflow`
+    /// `Error::TimestampOverflow`
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<FID128> {
+        loop {
+            let timestamp = self.new_timestamp()?;
+            match timestamp.cmp(&self.timestamp_last) {
+                cmp::Ordering::Less => return Err(Error::SysTimeIsInPast),
+                cmp::Ordering::Greater => {
+                    self.timestamp_last = timestamp;
+                    self.sequence = 0;
+                    return Ok(FID128::new(timestamp, 0, self.generator));
+                }
+                cmp::Ordering::Equal => {}
+            }
+            if self.sequence < cfg::SEQUENCE128_MAX {
+                self.sequence += 1;
+                return Ok(FID128::new(timestamp, self.sequence, self.generator));
+            } else if self.wait_sequence {
+                wait_next_timestamp(self.resolution, self.wait_spin, self.clock_resolution)?;
+            } else {
+                return Err(Error::Sequence128Overflow(self.sequence));
+            }
+        }
+    }
+
+    #[allow(clippy::absurd_extreme_comparisons)]
+    fn new_timestamp(&self) -> Result<u64> {
+        let timestamp = current_timestamp(self.timestamp_offset, self.resolution)?;
+        if timestamp > cfg::TIMESTAMP128_MAX {
+            Err(Error::TimestampOverflow(timestamp))
+        } else {
+            Ok(timestamp)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use config::test_constants::*;
+    use std::{thread, time};
+    use stubs::systemtime::SystemTime;
+
+    #[test]
+    fn next() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FID128GeneratorBuilder::new(u32::MAX)
+            .wait_sequence(false)
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().components(), (TIMESTAMP, 0, u32::MAX));
+        assert_eq!(gen.next().unwrap().components(), (TIMESTAMP, 1, u32::MAX));
+        gen.sequence = u32::MAX;
+        assert_eq!(gen.next().unwrap_err(), Error::Sequence128Overflow(u32::MAX));
+        gen.sequence = 0x10000;
+        assert_eq!(gen.next().unwrap().components(), (TIMESTAMP, 0x10001, u32::MAX));
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().components(), (TIMESTAMP + 1, 0, u32::MAX));
+        lock_sys_time.add(-2);
+        assert_eq!(gen.next().unwrap_err(), Error::SysTimeIsInPast);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn wait_sequence() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FID128GeneratorBuilder::new(GENERATOR as u32)
+            .sequence(cfg::SEQUENCE128_MAX)
+            .timestamp_last(TIMESTAMP)
+            .wait_spin(16)
+            .build()
+            .unwrap();
+        let handle = thread::spawn(move || {
+            thread::sleep(time::Duration::from_millis(10));
+            lock_sys_time.add(1);
+            lock_sys_time
+        });
+        let fid = gen.next().unwrap();
+        assert_eq!(fid.components(), (TIMESTAMP + 1, 0, GENERATOR as u32));
+        SystemTime::unlock(handle.join().unwrap());
+    }
+
+    #[test]
+    fn build() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        assert!(FID128GeneratorBuilder::new(cfg::GENERATOR128_MAX).build().is_ok());
+        assert_eq!(
+            FID128GeneratorBuilder::new(0)
+                .timestamp_last(TIMESTAMP + 1)
+                .build()
+                .unwrap()
+                .next()
+                .unwrap_err(),
+            Error::SysTimeIsInPast
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn timestamp_in_seconds() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = FID128GeneratorBuilder::new(GENERATOR as u32)
+            .timestamp_in_seconds()
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP / 1000);
+        let mut gen = FID128GeneratorBuilder::new(GENERATOR as u32)
+            .resolution(Resolution::Micros)
+            .build()
+            .unwrap();
+        assert_eq!(gen.next().unwrap().timestamp(), TIMESTAMP * 1000);
+        let future = cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() + TIMESTAMP as i64 / 1000 + 1;
+        assert_eq!(
+            FID128GeneratorBuilder::new(0)
+                .timestamp_offset(-future)
+                .build()
+                .unwrap_err(),
+            Error::SysTimeIsInPast
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+}
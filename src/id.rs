@@ -1,10 +1,21 @@
 //! Flower identificator
 
-use std::fmt;
+use core::cmp;
+use core::fmt;
+use core::ops::Deref;
+use core::time;
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use core::convert::TryFrom;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, Write};
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+use alloc::string::String;
 use base64;
 use config as cfg;
-use std::mem;
-use std;
+use core;
+use id_high::GeneratorHighFID;
 
 use {Error, Result};
 
@@ -12,6 +23,186 @@ use {Error, Result};
 #[derive(PartialEq, PartialOrd, Clone)]
 pub struct FID(u64);
 
+/// Base64 FID representation on stack
+///
+/// Dereferences to `&str` without validation or heap allocation,
+/// see `FID::to_b64_str`
+#[derive(Clone, Copy, PartialEq)]
+pub struct B64Str([u8; 11]);
+
+/// Split raw id into (timestamp, sequence, generator)
+///
+/// alike `FID::from(raw).components()`
+///
+/// # Examples
+/// ```
+/// use flowerid::id::decompose;
+/// assert_eq!(decompose(0x409b8b2ac6f2b12c), (0x204dc595637, 0x4ac, 0x12c));
+/// ```
+pub fn decompose(raw: u64) -> (u64, u16, u16) {
+    (
+        (raw & cfg::TIMESTAMP_MASK) >> cfg::TIMESTAMP_SHIFT,
+        ((raw & cfg::SEQUENCE_MASK) >> cfg::SEQUENCE_SHIFT) as u16,
+        (raw & cfg::GENERATOR_MASK) as u16,
+    )
+}
+
+/// Timestamp unit
+///
+/// `TIMESTAMP_LENGTH` bits of timestamp cover ~139 thousand years in seconds,
+/// ~139 years in milliseconds and only ~50.9 days in microseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    /// seconds
+    Seconds,
+    /// milliseconds
+    Millis,
+    /// microseconds
+    Micros,
+}
+
+impl Resolution {
+    /// Number of units in one second
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::Resolution;
+    /// assert_eq!(Resolution::Millis.per_second(), 1000);
+    /// ```
+    pub fn per_second(&self) -> u64 {
+        match *self {
+            Resolution::Seconds => 1,
+            Resolution::Millis => 1_000,
+            Resolution::Micros => 1_000_000,
+        }
+    }
+
+    /// Duration to units, truncated, saturates at `u64::MAX`
+    #[cfg(feature = "std")]
+    pub(crate) fn units(&self, duration: time::Duration) -> u64 {
+        self.checked_units(duration).unwrap_or(u64::MAX)
+    }
+
+    /// Duration to units, truncated, `None` on overflow
+    #[cfg(feature = "std")]
+    pub(crate) fn checked_units(&self, duration: time::Duration) -> Option<u64> {
+        let per_second = self.per_second();
+        duration
+            .as_secs()
+            .checked_mul(per_second)?
+            .checked_add(u64::from(duration.subsec_nanos()) / (1_000_000_000 / per_second))
+    }
+
+    /// Units to duration
+    pub(crate) fn duration(&self, units: u64) -> time::Duration {
+        let per_second = self.per_second();
+        time::Duration::from_secs(units / per_second)
+            + time::Duration::from_nanos(units % per_second * (1_000_000_000 / per_second))
+    }
+}
+
+impl Default for Resolution {
+    /// alike `gbuilder_defaults::TIMESTAMP_IN_SECONDS`
+    fn default() -> Resolution {
+        if cfg::gbuilder_defaults::TIMESTAMP_IN_SECONDS {
+            Resolution::Seconds
+        } else {
+            Resolution::Millis
+        }
+    }
+}
+
+/// Every FID in `start..end` in id order
+///
+/// Bounds are clamped to 63 bits used by timestamp, sequence and generator,
+/// iteration crosses field boundaries. Empty if `start >= end`
+///
+/// # Examples
+/// ```
+/// use flowerid::id::{self, FID};
+/// let start = FID::new(0x204dc595637, 0x7ff, 0x3fe).unwrap();
+/// let end = FID::new(0x204dc595638, 0, 1).unwrap();
+/// let ids: Vec<FID> = id::range(start.clone(), end.clone()).collect();
+/// assert_eq!(ids.len(), 3);
+/// assert_eq!(ids[2], FID::new(0x204dc595638, 0, 0).unwrap());
+/// assert_eq!(id::range(end, start).count(), 0);
+/// ```
+pub fn range(start: FID, end: FID) -> impl Iterator<Item = FID> {
+    let end_max = (cfg::TIMESTAMP_MASK | cfg::SEQUENCE_MASK | cfg::GENERATOR_MASK) + 1;
+    (cmp::min(start.0, end_max)..cmp::min(end.0, end_max)).map(FID)
+}
+
+/// Lazily parse newline-separated base64 FIDs
+///
+/// Lines are trimmed, empty lines are skipped. Each item is either parsed FID
+/// or `from_string` error, `Error::IoError` on read failure
+///
+/// # Examples
+/// ```
+/// use flowerid::id::{self, FID};
+/// let data = "0I3FXzb0sSw\n\n  0I3FXzb0sSw  \n";
+/// let ids: Vec<FID> = id::read_ids(data.as_bytes()).map(|x| x.unwrap()).collect();
+/// assert_eq!(ids.len(), 2);
+/// assert_eq!(ids[1].to_string(), "0I3FXzb0sSw");
+/// ```
+#[cfg(feature = "std")]
+pub fn read_ids<R: BufRead>(reader: R) -> impl Iterator<Item = Result<FID>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(ref x) if x.trim().is_empty() => None,
+        Ok(x) => Some(FID::from_string(x.trim())),
+        Err(_) => Some(Err(Error::IoError)),
+    })
+}
+
+/// Write FIDs as newline-separated base64
+///
+/// Counterpart of `read_ids`, no allocation per FID
+///
+/// # Examples
+/// ```
+/// use flowerid::id::{self, FID};
+/// let mut out = Vec::new();
+/// id::write_ids(vec![FID::nil(), FID::nil()], &mut out).unwrap();
+/// assert_eq!(out, b"AAAAAAAAAAA\nAAAAAAAAAAA\n");
+/// ```
+#[cfg(feature = "std")]
+pub fn write_ids<W: Write>(ids: impl IntoIterator<Item = FID>, mut writer: W) -> io::Result<()> {
+    let mut buf = [b'\n'; 12];
+    for x in ids {
+        x.to_b64_into(&mut buf).unwrap();
+        writer.write_all(&buf)?;
+    }
+    Ok(())
+}
+
+/// Join (timestamp, sequence, generator) into raw id
+///
+/// alike `FID::new(..).map(u64::from)`
+///
+/// # Failures
+/// `Error::TimestampOverflow`
+/// `Error::SequenceOverflow`
+/// `Error::GeneratorOverflow`
+///
+/// # Examples
+/// ```
+/// use flowerid::id::compose;
+/// assert_eq!(compose(0x204dc595637, 0x4ac, 0x12c).unwrap(), 0x409b8b2ac6f2b12c);
+/// ```
+pub fn compose(timestamp: u64, sequence: u16, generator: u16) -> Result<u64> {
+    if timestamp > cfg::TIMESTAMP_MAX {
+        Err(Error::TimestampOverflow(timestamp))
+    } else if sequence > cfg::SEQUENCE_MAX {
+        Err(Error::SequenceOverflow(sequence))
+    } else if generator > cfg::GENERATOR_MAX {
+        Err(Error::GeneratorOverflow(generator))
+    } else {
+        Ok((timestamp << cfg::TIMESTAMP_SHIFT)
+            | ((sequence as u64) << cfg::SEQUENCE_SHIFT)
+            | (generator as u64))
+    }
+}
+
 impl FID {
     /// Create FID from components
     ///
@@ -34,22 +225,80 @@ impl FID {
     /// );
     /// ```
     pub fn new(timestamp: u64, sequence: u16, generator: u16) -> Result<FID> {
-        if timestamp >= 1 << cfg::TIMESTAMP_LENGTH {
-            Err(Error::TimestampOverflow(timestamp))
-        } else if sequence >= 1 << cfg::SEQUENCE_LENGTH {
-            Err(Error::SequenceOverflow(sequence))
-        } else if generator >= 1 << cfg::GENERATOR_LENGTH {
-            Err(Error::GeneratorOverflow(generator))
-        } else {
-            Ok(FID(
-                (timestamp << (cfg::SEQUENCE_LENGTH + cfg::GENERATOR_LENGTH))
-                    | ((sequence as u64) << cfg::GENERATOR_LENGTH)
-                    | (generator as u64),
-            ))
-        }
+        compose(timestamp, sequence, generator).map(FID)
+    }
+
+    /// Create FID from components, `None` if any component overflows
+    ///
+    /// alike `FID::new(..).ok()`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// assert!(FID::checked(0x204dc595637, 0x4ac, 0x12c).is_some());
+    /// assert!(FID::checked(0x204dc595637, 0x800, 0x12c).is_none());
+    /// ```
+    pub fn checked(timestamp: u64, sequence: u16, generator: u16) -> Option<FID> {
+        FID::new(timestamp, sequence, generator).ok()
+    }
+
+    /// Create FID from components in const context
+    ///
+    /// **WARNING** components are not validated: out of range bits are silently truncated,
+    /// use `FID::new` unless components are known at compile time
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// const FID_CONST: FID = FID::new_const(0x204dc595637, 0x4ac, 0x12c);
+    /// assert_eq!(FID_CONST, FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap());
+    /// ```
+    pub const fn new_const(timestamp: u64, sequence: u16, generator: u16) -> FID {
+        FID(
+            ((timestamp << cfg::TIMESTAMP_SHIFT) & cfg::TIMESTAMP_MASK)
+                | (((sequence as u64) << cfg::SEQUENCE_SHIFT) & cfg::SEQUENCE_MASK)
+                | ((generator as u64) & cfg::GENERATOR_MASK),
+        )
+    }
+
+    /// Create FID from u64 in const context
+    ///
+    /// alike `From<u64>`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// const SENTINEL: FID = FID::from_u64_const(0);
+    /// assert_eq!(SENTINEL, FID::from(0));
+    /// ```
+    pub const fn from_u64_const(id: u64) -> FID {
+        FID(id)
+    }
+
+    /// Nil FID sentinel ("unset" placeholder)
+    ///
+    /// timestamp, sequence and generator are all zero. Generated ids are never nil:
+    /// with an epoch in the past timestamp of a real id is > 0
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// assert!(FID::nil().is_nil());
+    /// assert_eq!(FID::nil().components(), (0, 0, 0));
+    /// assert!(!FID::new(1, 0, 0).unwrap().is_nil());
+    /// ```
+    pub const fn nil() -> FID {
+        FID(0)
+    }
+
+    /// Check FID is nil
+    ///
+    /// see `FID::nil`
+    pub fn is_nil(&self) -> bool {
+        self.0 == 0
     }
 
-    /// Serialize FID
+    /// Serialize FID (big-endian)
     ///
     /// # Examples
     /// ```
@@ -61,15 +310,10 @@ impl FID {
     /// );
     /// ```
     pub fn to_bytes(&self) -> [u8; 8] {
-        unsafe {
-            let mut res: [u8; 8] = mem::uninitialized();
-            let ptr = res.as_mut_ptr() as *mut u64;
-            *ptr = self.0.to_be();
-            return res;
-        }
+        self.0.to_be_bytes()
     }
 
-    /// Deserialize FID
+    /// Deserialize FID (big-endian)
     ///
     /// # Examples
     /// ```
@@ -84,7 +328,7 @@ impl FID {
         FID::from_slice(val).unwrap()
     }
 
-    /// Deserialize FID
+    /// Deserialize FID (big-endian)
     ///
     /// # Failures
     /// `Error::WrongSliceSize` if slice length != 8
@@ -103,10 +347,130 @@ impl FID {
         if val.len() != 8 {
             return Err(Error::WrongSliceSize(val.len()));
         }
-        unsafe {
-            let tmp = *(val.as_ptr() as *const u64);
-            Ok(FID(u64::from_be(tmp)))
+        let mut tmp = [0u8; 8];
+        tmp.copy_from_slice(val);
+        Ok(FID(u64::from_be_bytes(tmp)))
+    }
+
+    /// Serialize FID (big-endian) into first 8 bytes of buffer
+    ///
+    /// # Failures
+    /// `Error::WrongSliceSize` if buffer length < 8
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// let mut buffer = [0u8; 10];
+    /// fid.write_bytes(&mut buffer[1..]).unwrap();
+    /// assert_eq!(
+    ///     &buffer,
+    ///     b"\x00@\x9b\x8b*\xc6\xf2\xb1,\x00"
+    /// );
+    /// ```
+    pub fn write_bytes(&self, buf: &mut [u8]) -> Result<()> {
+        if buf.len() < 8 {
+            return Err(Error::WrongSliceSize(buf.len()));
+        }
+        buf[..8].copy_from_slice(&self.to_bytes());
+        Ok(())
+    }
+
+    /// Serialize FID (little-endian)
+    ///
+    /// **WARNING** big-endian `to_bytes` is the default FID binary serialization
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(
+    ///     &fid.to_bytes_le(),
+    ///     b",\xb1\xf2\xc6*\x8b\x9b@"
+    /// );
+    /// ```
+    pub fn to_bytes_le(&self) -> [u8; 8] {
+        self.0.to_le_bytes()
+    }
+
+    /// Deserialize FID (little-endian)
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_bytes_le(&b",\xb1\xf2\xc6*\x8b\x9b@");
+    /// assert_eq!(
+    ///     format!("{}", fid),
+    ///     "QJuLKsbysSw"
+    /// );
+    /// ```
+    pub fn from_bytes_le(val: &[u8; 8]) -> FID {
+        FID(u64::from_le_bytes(*val))
+    }
+
+    /// Deserialize FID (little-endian)
+    ///
+    /// # Failures
+    /// `Error::WrongSliceSize` if slice length != 8
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let val = b",\xb1\xf2\xc6*\x8b\x9b@";
+    /// let fid = FID::from_slice_le(val).unwrap();
+    /// assert_eq!(
+    ///     format!("{}", fid),
+    ///     "QJuLKsbysSw"
+    /// );
+    /// ```
+    pub fn from_slice_le(val: &[u8]) -> Result<FID> {
+        if val.len() != 8 {
+            return Err(Error::WrongSliceSize(val.len()));
+        }
+        let mut tmp = [0u8; 8];
+        tmp.copy_from_slice(val);
+        Ok(FID::from_bytes_le(&tmp))
+    }
+
+    /// Serialize FID into UUID-sized 16 bytes
+    ///
+    /// bytes 0..8 are `to_bytes` (big-endian), bytes 8..16 are zero
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(
+    ///     &fid.to_uuid_bytes(),
+    ///     b"@\x9b\x8b*\xc6\xf2\xb1,\0\0\0\0\0\0\0\0"
+    /// );
+    /// ```
+    pub fn to_uuid_bytes(&self) -> [u8; 16] {
+        let mut result = [0u8; 16];
+        result[..8].copy_from_slice(&self.to_bytes());
+        result
+    }
+
+    /// Deserialize FID from UUID-sized 16 bytes
+    ///
+    /// see `to_uuid_bytes`
+    ///
+    /// # Failures
+    /// `Error::InvalidUuid` bytes 8..16 are not zero
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(FID::from_uuid_bytes(&fid.to_uuid_bytes()).unwrap(), fid);
+    /// ```
+    pub fn from_uuid_bytes(val: &[u8; 16]) -> Result<FID> {
+        if val[8..].iter().any(|x| *x != 0) {
+            return Err(Error::InvalidUuid);
         }
+        let mut tmp = [0u8; 8];
+        tmp.copy_from_slice(&val[..8]);
+        Ok(FID::from_bytes(&tmp))
     }
 
     /// Serialize FID to base64 string
@@ -120,15 +484,16 @@ impl FID {
     ///     "QJuLKsbysSw"
     /// );
     /// ```
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
-        let b64 = self.to_b64();
-        std::str::from_utf8(&b64).unwrap().to_string()
+        String::from(&*self.to_b64_str())
     }
 
     /// Deserialize FID from base64 string
     ///
     /// # Failures
-    /// `Error::WrongSliceSize` if decoded length != 8
+    /// `Error::WrongSliceSize` if input length isn't 11 (or 12 with trailing `=`)
+    /// or input decodes to other than 8 bytes
     /// `Error::Base64WrongSymbolError` decoding failed
     ///
     /// # Examples
@@ -145,6 +510,39 @@ impl FID {
         FID::from_b64(val)
     }
 
+    /// Deserialize FID from base64, decimal or hex integer string
+    ///
+    /// Precedence rules:
+    /// * `0x` prefixed string is parsed as hex integer
+    /// * all-digit string which length isn't 11 is parsed as decimal integer
+    /// * anything else (all-digit 11 symbols string too) is parsed as base64
+    ///
+    /// # Failures
+    /// `Error::IntegerParseError` integer parsing failed
+    /// alike `FID::from_string`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_string("QJuLKsbysSw").unwrap();
+    /// assert_eq!(FID::parse_any("QJuLKsbysSw").unwrap(), fid);
+    /// assert_eq!(FID::parse_any("4655467655660220716").unwrap(), fid);
+    /// assert_eq!(FID::parse_any("0x409b8b2ac6f2b12c").unwrap(), fid);
+    /// ```
+    pub fn parse_any(val: &str) -> Result<FID> {
+        if let Some(hex) = val.strip_prefix("0x") {
+            u64::from_str_radix(hex, 16)
+                .map(FID)
+                .map_err(|_| Error::IntegerParseError)
+        } else if val.len() != 11 && !val.is_empty() && val.bytes().all(|x| x.is_ascii_digit()) {
+            val.parse::<u64>()
+                .map(FID)
+                .map_err(|_| Error::IntegerParseError)
+        } else {
+            FID::from_string(val)
+        }
+    }
+
     /// Serialize FID to base64
     ///
     /// # Examples
@@ -162,94 +560,811 @@ impl FID {
         buffer
     }
 
+    /// Serialize FID to base64 into start of buf
+    ///
+    /// Returns number of written bytes (always 11)
+    ///
+    /// # Failures
+    /// `Error::Base64BufferTooSmall` if buf is shorter than 11 bytes
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// let mut buf = [b'.'; 12];
+    /// assert_eq!(fid.to_b64_into(&mut buf), Ok(11));
+    /// assert_eq!(&buf, b"QJuLKsbysSw.");
+    /// ```
+    pub fn to_b64_into(&self, buf: &mut [u8]) -> Result<usize> {
+        if buf.len() < 11 {
+            return Err(Error::Base64BufferTooSmall);
+        }
+        base64::urlsafe_encode_without_pading_into(&self.to_bytes(), &mut buf[..11])
+    }
+
+    /// Serialize FID to base64 string on stack
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(fid.to_b64_str(), "QJuLKsbysSw");
+    /// ```
+    pub fn to_b64_str(&self) -> B64Str {
+        B64Str(self.to_b64())
+    }
+
+    /// Lightweight base64 formatter for `write!` / `format!` without `String`
+    ///
+    /// Formats on stack alike `Display` of FID, padding and alignment are respected
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fmt::Write;
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// let mut out = String::new();
+    /// write!(out, "id={} ", fid.display()).unwrap();
+    /// write!(out, "[{:>12}]", fid.display()).unwrap();
+    /// assert_eq!(out, "id=QJuLKsbysSw [ QJuLKsbysSw]");
+    /// ```
+    pub fn display(&self) -> impl fmt::Display {
+        self.to_b64_str()
+    }
+
+    /// Serialize FID to base64 into a thread-local buffer and pass it to `f`
+    ///
+    /// `&str` is valid only inside `f` and is made without heap allocation.
+    /// Nested calls from inside `f` use a stack buffer instead of the
+    /// thread-local one, see `to_b64_str`.
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(fid.with_b64_cached(|s| s.len()), 11);
+    /// fid.with_b64_cached(|s| assert_eq!(s, "QJuLKsbysSw"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_b64_cached<R, F: FnOnce(&str) -> R>(&self, f: F) -> R {
+        thread_local! {
+            static BUFFER: RefCell<[u8; 11]> = const { RefCell::new([0u8; 11]) };
+        }
+        BUFFER.with(|buffer| match buffer.try_borrow_mut() {
+            Ok(mut buffer) => {
+                *buffer = self.to_b64();
+                // base64 alphabet is ASCII
+                f(unsafe { core::str::from_utf8_unchecked(&*buffer) })
+            }
+            Err(_) => f(&self.to_b64_str()),
+        })
+    }
+
     /// Deserialize FID from base64
     ///
     /// # Failures
-    /// `Error::WrongSliceSize` if decoded length != 8
+    /// `Error::WrongSliceSize` if input length isn't 11 (or 12 with trailing `=`)
+    /// or input decodes to other than 8 bytes
     /// `Error::Base64WrongSymbolError` decoding failed
     ///
     /// # Examples
     /// ```
+    /// use flowerid::Error;
     /// use flowerid::id::FID;
     /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
     /// assert_eq!(
     ///     format!("{}", fid),
     ///     "QJuLKsbysSw"
     /// );
+    /// assert_eq!(FID::from_b64(b"QJuLKsbysSw=").unwrap(), fid);
+    /// assert_eq!(FID::from_b64(b"QJuLKsbysSwAAAA").unwrap_err(), Error::WrongSliceSize(15));
     /// ```
     pub fn from_b64(val: &[u8]) -> Result<FID> {
+        if !(val.len() == 11 || (val.len() == 12 && val[11] == b'=')) {
+            return Err(Error::WrongSliceSize(val.len()));
+        }
         let mut buffer = [0u8; 8];
-        base64::decode_into(val, Some(Error::Base64PaddingError), &mut buffer)?;
+        let len = base64::decode_into(val, Some(Error::Base64PaddingError), &mut buffer)?;
+        if len != buffer.len() {
+            return Err(Error::WrongSliceSize(len));
+        }
         Ok(FID::from_bytes(&buffer))
     }
 
-    /// timestamp getter
+    /// Deserialize FID from canonical base64 only
+    ///
+    /// Alike `from_b64` but accepts only the string `to_b64` produces (with
+    /// optional trailing `=`), so distinct strings never decode to the same FID
+    ///
+    /// # Failures
+    /// `Error::Base64NonCanonical` input decodes but isn't canonical
+    /// alike `from_b64`
     ///
     /// # Examples
     /// ```
+    /// use flowerid::Error;
     /// use flowerid::id::FID;
-    /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
-    /// assert_eq!(
-    ///     fid.timestamp(),
-    ///     0x204dc595637
-    /// );
+    /// let fid = FID::from_b64_canonical(b"QJuLKsbysSw").unwrap();
+    /// assert_eq!(FID::from_b64(b"QJuLKsbysSx").unwrap(), fid);
+    /// assert_eq!(FID::from_b64_canonical(b"QJuLKsbysSx").unwrap_err(), Error::Base64NonCanonical);
     /// ```
-    pub fn timestamp(&self) -> u64 {
-        (self.0 & cfg::TIMESTAMP_MASK) >> (cfg::GENERATOR_LENGTH + cfg::SEQUENCE_LENGTH)
+    pub fn from_b64_canonical(val: &[u8]) -> Result<FID> {
+        let fid = FID::from_b64(val)?;
+        if val[..11] != fid.to_b64() {
+            return Err(Error::Base64NonCanonical);
+        }
+        Ok(fid)
     }
 
-    /// sequence getter
+    /// Deserialize FID from exactly 11 chars of unpadded base64
+    ///
+    /// Faster `from_b64` for the canonical form, low 2 bits of last char are ignored
+    ///
+    /// # Failures
+    /// `Error::Base64WrongSymbolError` decoding failed
     ///
     /// # Examples
     /// ```
     /// use flowerid::id::FID;
-    /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
-    /// assert_eq!(
-    ///     fid.sequence(),
-    ///     0x4ac
-    /// );
+    /// let fid = FID::from_b64_exact(b"QJuLKsbysSw").unwrap();
+    /// assert_eq!(fid, FID::from_b64(b"QJuLKsbysSw").unwrap());
     /// ```
-    pub fn sequence(&self) -> u16 {
-        ((self.0 & cfg::SEQUENCE_MASK) >> cfg::GENERATOR_LENGTH) as u16
+    pub fn from_b64_exact(val: &[u8; 11]) -> Result<FID> {
+        let mut id = 0u64;
+        for x in val[..10].iter() {
+            let x = base64::decode_char(*x).ok_or(Error::Base64WrongSymbolError)?;
+            id = (id << 6) | x as u64;
+        }
+        let x = base64::decode_char(val[10]).ok_or(Error::Base64WrongSymbolError)?;
+        Ok(FID((id << 4) | (x >> 2) as u64))
     }
 
-    /// generator getter
+    /// Check string is 11 chars of URL-safe base64 FID without decoding
     ///
     /// # Examples
     /// ```
     /// use flowerid::id::FID;
-    /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
-    /// assert_eq!(
-    ///     fid.generator(),
-    ///     0x12c
-    /// );
+    /// assert!(FID::is_valid_str("QJuLKsbysSw"));
+    /// assert!(!FID::is_valid_str("QJuLKsbysS"));
+    /// assert!(!FID::is_valid_str("QJuLKsbys+w"));
     /// ```
-    pub fn generator(&self) -> u16 {
-        (self.0 & cfg::GENERATOR_MASK) as u16
+    pub fn is_valid_str(s: &str) -> bool {
+        s.len() == 11
+            && s.bytes()
+                .all(|x| x.is_ascii_alphanumeric() || x == b'-' || x == b'_')
     }
-}
 
-impl fmt::Debug for FID {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "FID{{ id: \"{}\"; ts: {}; seq: {}; gen: {} }}",
-            self,
-            self.timestamp(),
-            self.sequence(),
-            self.generator()
-        )
+    /// Serialize FID to base64 with sortable alphabet
+    ///
+    /// Unlike `to_b64` strings sort the same way as FIDs do
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(&fid.to_b64_sortable(), b"F8iA9gQmgHk");
+    /// let next = FID::new(0x204dc595637, 0x4ad, 0x12c).unwrap();
+    /// assert!(next.to_b64_sortable() > fid.to_b64_sortable());
+    /// ```
+    pub fn to_b64_sortable(&self) -> [u8; 11] {
+        let mut buffer = [0u8; 11];
+        base64::sortable_encode_without_pading_into(&self.to_bytes(), &mut buffer).unwrap();
+        buffer
     }
-}
 
-impl fmt::Display for FID {
+    /// Deserialize FID from base64 with sortable alphabet
+    ///
+    /// # Failures
+    /// `Error::WrongSliceSize` if input length isn't 11 or it doesn't decode to 8 bytes
+    /// `Error::Base64WrongSymbolError` decoding failed
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_b64_sortable(b"F8iA9gQmgHk").unwrap();
+    /// assert_eq!(fid, FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap());
+    /// ```
+    pub fn from_b64_sortable(val: &[u8]) -> Result<FID> {
+        if val.len() != 11 {
+            return Err(Error::WrongSliceSize(val.len()));
+        }
+        let mut buffer = [0u8; 8];
+        let len = base64::sortable_decode_into(val, Some(Error::Base64PaddingError), &mut buffer)?;
+        if len != buffer.len() {
+            return Err(Error::WrongSliceSize(len));
+        }
+        Ok(FID::from_bytes(&buffer))
+    }
+
+    /// timestamp getter
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
+    /// assert_eq!(
+    ///     fid.timestamp(),
+    ///     0x204dc595637
+    /// );
+    /// ```
+    pub fn timestamp(&self) -> u64 {
+        (self.0 & cfg::TIMESTAMP_MASK) >> cfg::TIMESTAMP_SHIFT
+    }
+
+    /// sequence getter
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
+    /// assert_eq!(
+    ///     fid.sequence(),
+    ///     0x4ac
+    /// );
+    /// ```
+    pub fn sequence(&self) -> u16 {
+        ((self.0 & cfg::SEQUENCE_MASK) >> cfg::SEQUENCE_SHIFT) as u16
+    }
+
+    /// generator getter
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
+    /// assert_eq!(
+    ///     fid.generator(),
+    ///     0x12c
+    /// );
+    /// ```
+    pub fn generator(&self) -> u16 {
+        (self.0 & cfg::GENERATOR_MASK) as u16
+    }
+
+    /// (timestamp, sequence, generator) getter
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_b64(b"QJuLKsbysSw").unwrap();
+    /// assert_eq!(
+    ///     fid.components(),
+    ///     (0x204dc595637, 0x4ac, 0x12c)
+    /// );
+    /// ```
+    pub fn components(&self) -> (u64, u16, u16) {
+        decompose(self.0)
+    }
+
+    /// Raw value with generator bits zeroed
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(
+    ///     fid.without_generator(),
+    ///     FID::new(0x204dc595637, 0x4ac, 0).unwrap()
+    /// );
+    /// ```
+    pub fn without_generator(&self) -> u64 {
+        self.0 & (cfg::TIMESTAMP_MASK | cfg::SEQUENCE_MASK)
+    }
+
+    /// Copy of FID with reserved high bit set to parity of lower 63 bits
+    ///
+    /// Getters ignore the high bit. **WARNING** raw order of such ids
+    /// doesn't follow timestamp order.
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap().with_parity();
+    /// assert!(fid.verify_parity());
+    /// assert_eq!(fid.components(), (0x204dc595637, 0x4ac, 0x12c));
+    /// ```
+    pub fn with_parity(&self) -> FID {
+        let id = self.0 & !cfg::PARITY_MASK;
+        FID(id | u64::from(id.count_ones() % 2) << 63)
+    }
+
+    /// Check reserved high bit matches parity of lower 63 bits
+    ///
+    /// Detects single bit corruption of ids created with `FID::with_parity`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap().with_parity();
+    /// let corrupted = FID::from(u64::from(fid) ^ 0x100);
+    /// assert!(!corrupted.verify_parity());
+    /// ```
+    pub fn verify_parity(&self) -> bool {
+        self.0.count_ones() & 1 == 0
+    }
+
+    /// Compare timestamp and sequence ignoring generator
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert!(fid.same_moment(&FID::new(0x204dc595637, 0x4ac, 0x47).unwrap()));
+    /// assert!(!fid.same_moment(&FID::new(0x204dc595637, 0x4ad, 0x12c).unwrap()));
+    /// ```
+    pub fn same_moment(&self, other: &FID) -> bool {
+        self.without_generator() == other.without_generator()
+    }
+
+    /// Smallest FID greater than self (exclusive lower bound for keyset pagination)
+    ///
+    /// None if self is the largest FID
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x3ff).unwrap();
+    /// assert_eq!(fid.next_value(), Some(FID::new(0x204dc595637, 0x4ad, 0).unwrap()));
+    /// ```
+    pub fn next_value(&self) -> Option<FID> {
+        let max = cfg::TIMESTAMP_MASK | cfg::SEQUENCE_MASK | cfg::GENERATOR_MASK;
+        if self.0 >= max {
+            None
+        } else {
+            Some(FID(self.0 + 1))
+        }
+    }
+
+    /// Reorder fields to generator-high layout
+    ///
+    /// Layout is `| parity | generator | sequence | timestamp |`, so numeric
+    /// prefix of raw id selects generator. Inverse is `GeneratorHighFID::to_fid`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// let high = fid.to_generator_high();
+    /// assert_eq!(high.components(), fid.components());
+    /// assert_eq!(u64::from(high.clone()) >> 53, 0x12c);
+    /// assert_eq!(high.to_fid(), fid);
+    /// ```
+    pub fn to_generator_high(&self) -> GeneratorHighFID {
+        GeneratorHighFID::from(self.clone())
+    }
+
+    /// Layout version tag from reserved high bit
+    ///
+    /// 0 is current layout. Meaningful only for ids of generators with
+    /// `FIDGeneratorBuilder::version` (reserved bit is parity otherwise).
+    /// Getters ignore the tag, so v0 fields decode identically with and
+    /// without it
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(fid.version(), 0);
+    /// let tagged = fid.with_version(1).unwrap();
+    /// assert_eq!(tagged.version(), 1);
+    /// assert_eq!(tagged.components(), fid.components());
+    /// ```
+    pub fn version(&self) -> u8 {
+        ((self.0 & cfg::VERSION_MASK) >> 63) as u8
+    }
+
+    /// Copy of FID with layout version tag
+    ///
+    /// # Failures
+    /// `Error::InvalidVersion` if version is greater than `VERSION_MAX`
+    pub fn with_version(&self, version: u8) -> Result<FID> {
+        if version > cfg::VERSION_MAX {
+            return Err(Error::InvalidVersion(version));
+        }
+        Ok(FID(self.0 & !cfg::VERSION_MASK | u64::from(version) << 63))
+    }
+
+    /// Largest FID less than self
+    ///
+    /// None if self is nil
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ad, 0).unwrap();
+    /// assert_eq!(fid.prev_value(), Some(FID::new(0x204dc595637, 0x4ac, 0x3ff).unwrap()));
+    /// assert_eq!(FID::nil().prev_value(), None);
+    /// ```
+    pub fn prev_value(&self) -> Option<FID> {
+        self.0.checked_sub(1).map(FID)
+    }
+
+    /// Add n to raw integer value
+    ///
+    /// Addition carries across field boundaries: sequence overflow increments
+    /// timestamp and so on. None if result doesn't fit 63 bits used by
+    /// timestamp, sequence and generator
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x7ff, 0x3ff).unwrap();
+    /// assert_eq!(fid.checked_add_raw(1), Some(FID::new(0x204dc595638, 0, 0).unwrap()));
+    /// assert_eq!(fid.checked_add_raw(u64::max_value()), None);
+    /// ```
+    pub fn checked_add_raw(&self, n: u64) -> Option<FID> {
+        let max = cfg::TIMESTAMP_MASK | cfg::SEQUENCE_MASK | cfg::GENERATOR_MASK;
+        match self.0.checked_add(n) {
+            Some(x) if x <= max => Some(FID(x)),
+            _ => None,
+        }
+    }
+
+    /// Add n to raw integer value, clamping at largest FID
+    ///
+    /// Same carry semantics as `checked_add_raw`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// use flowerid::config::{TIMESTAMP_MAX, SEQUENCE_MAX, GENERATOR_MAX};
+    /// let max = FID::new(TIMESTAMP_MAX, SEQUENCE_MAX, GENERATOR_MAX).unwrap();
+    /// assert_eq!(FID::nil().saturating_add_raw(u64::max_value()), max);
+    /// assert_eq!(FID::nil().saturating_add_raw(0x400).components(), (0, 1, 0));
+    /// ```
+    pub fn saturating_add_raw(&self, n: u64) -> FID {
+        let max = cfg::TIMESTAMP_MASK | cfg::SEQUENCE_MASK | cfg::GENERATOR_MASK;
+        FID(cmp::min(self.0.saturating_add(n), max))
+    }
+
+    /// Bounds of generator field for generators `lo..=hi`
+    ///
+    /// Raw id belongs to range if `lo_bound <= raw & GENERATOR_MASK <= hi_bound`
+    ///
+    /// # Failures
+    /// `Error::GeneratorOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::{FID, GENERATOR_MASK};
+    /// let (lo, hi) = FID::masked_generator_range(0, 15).unwrap();
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0xc).unwrap();
+    /// let raw = u64::from(fid);
+    /// assert!(lo <= raw & GENERATOR_MASK && raw & GENERATOR_MASK <= hi);
+    /// ```
+    pub fn masked_generator_range(lo: u16, hi: u16) -> Result<(u64, u64)> {
+        let lo = FID::new(0, 0, lo)?;
+        let hi = FID::new(0, 0, hi)?;
+        Ok((lo.0 & cfg::GENERATOR_MASK, hi.0 & cfg::GENERATOR_MASK))
+    }
+
+    /// Deterministic FID from 64-bit content hash (idempotency / dedup key)
+    ///
+    /// Hash is masked to 63 bits used by timestamp, sequence and generator,
+    /// so equal hashes give equal FIDs.
+    /// **WARNING** such ids aren't time-ordered nor monotonic, don't mix them
+    /// with generator-produced ids for ordering
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::from_hash(0xdead_beef_dead_beef);
+    /// assert_eq!(fid, FID::from_hash(0xdead_beef_dead_beef));
+    /// assert_eq!(u64::from(fid), 0x5ead_beef_dead_beef);
+    /// ```
+    pub fn from_hash(hash: u64) -> FID {
+        FID(hash & (cfg::TIMESTAMP_MASK | cfg::SEQUENCE_MASK | cfg::GENERATOR_MASK))
+    }
+
+    /// Smallest and largest FID with given timestamp
+    ///
+    /// `lo <= id <= hi` selects exactly ids of that timestamp
+    ///
+    /// # Failures
+    /// `Error::TimestampOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let (lo, hi) = FID::range_for_timestamp(0x204dc595637).unwrap();
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert!(lo <= fid && fid <= hi);
+    /// assert!(FID::new(0x204dc595638, 0, 0).unwrap() > hi);
+    /// ```
+    pub fn range_for_timestamp(timestamp: u64) -> Result<(FID, FID)> {
+        Ok((
+            FID::new(timestamp, 0, 0)?,
+            FID::new(timestamp, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX)?,
+        ))
+    }
+
+    /// All FIDs of one timestamp and generator in sequence order
+    ///
+    /// # Failures
+    /// `Error::TimestampOverflow`
+    /// `Error::GeneratorOverflow`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let ids: Vec<FID> = FID::sequences_for(0x204dc595637, 0x12c).unwrap().collect();
+    /// assert_eq!(ids.len(), 2048);
+    /// assert_eq!(ids[0x4ac], FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap());
+    /// ```
+    pub fn sequences_for(timestamp: u64, generator: u16) -> Result<impl Iterator<Item = FID>> {
+        let first = FID::new(timestamp, 0, generator)?;
+        Ok((0..=u64::from(cfg::SEQUENCE_MAX))
+            .map(move |sequence| FID(first.0 | sequence << cfg::SEQUENCE_SHIFT)))
+    }
+
+    /// Timestamp as duration since generator epoch (offset-adjusted)
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use flowerid::id::{FID, Resolution};
+    /// let fid = FID::new(1_500, 0, 0).unwrap();
+    /// assert_eq!(fid.timestamp_duration(Resolution::Millis), Duration::from_millis(1_500));
+    /// assert_eq!(fid.timestamp_duration(Resolution::Seconds), Duration::from_secs(1_500));
+    /// ```
+    pub fn timestamp_duration(&self, resolution: Resolution) -> time::Duration {
+        resolution.duration(self.timestamp())
+    }
+
+    /// Smallest FID of given moment, suitable as query bound
+    ///
+    /// offset_secs and resolution must match generator settings. chrono
+    /// `DateTime<Utc>` converts into `SystemTime` with `From`
+    ///
+    /// # Failures
+    /// `Error::SysTimeIsInPast` if time is before generator epoch,
+    /// `Error::TimestampOverflow` (with `u64::MAX` if it doesn't fit `u64` either)
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use flowerid::id::{FID, Resolution};
+    /// let time = UNIX_EPOCH + Duration::from_millis(1_500);
+    /// let fid = FID::from_system_time(time, 10, Resolution::Millis).unwrap();
+    /// assert_eq!(fid.components(), (11_500, 0, 0));
+    /// assert_eq!(fid.to_system_time(10, Resolution::Millis), Some(time));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_system_time(
+        time: SystemTime,
+        offset_secs: i64,
+        resolution: Resolution,
+    ) -> Result<FID> {
+        let epoch = match offset_epoch(offset_secs) {
+            Some(epoch) => epoch,
+            // unrepresentable epoch is after any time for negative offset
+            None if offset_secs < 0 => return Err(Error::SysTimeIsInPast),
+            None => return Err(Error::TimestampOverflow(u64::MAX)),
+        };
+        let since_epoch = time
+            .duration_since(epoch)
+            .map_err(|_| Error::SysTimeIsInPast)?;
+        let timestamp = resolution
+            .checked_units(since_epoch)
+            .ok_or(Error::TimestampOverflow(u64::MAX))?;
+        FID::new(timestamp, 0, 0)
+    }
+
+    /// Moment of FID timestamp
+    ///
+    /// offset_secs and resolution must match generator settings,
+    /// `None` if moment isn't representable by `SystemTime`
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use flowerid::id::{FID, Resolution};
+    /// let fid = FID::new(60, 0x4ac, 0x12c).unwrap();
+    /// let time = fid.to_system_time(-60, Resolution::Seconds).unwrap();
+    /// assert_eq!(time, UNIX_EPOCH + Duration::from_secs(120));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_system_time(&self, offset_secs: i64, resolution: Resolution) -> Option<SystemTime> {
+        offset_epoch(offset_secs)?.checked_add(self.timestamp_duration(resolution))
+    }
+
+    /// Compare moments of FIDs from generators with different settings
+    ///
+    /// Both timestamps are converted to unix time with own resolution and offset.
+    /// Sequence and generator are ignored, FIDs of the same moment are equal
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use flowerid::id::{FID, Resolution};
+    /// let millis = FID::new(1_500, 0, 1).unwrap();
+    /// let seconds = FID::new(2, 0, 2).unwrap();
+    /// let ordering = millis.compare_wall_clock(Resolution::Millis, 0, &seconds, Resolution::Seconds, 0);
+    /// assert_eq!(ordering, Ordering::Less);
+    /// let ordering = millis.compare_wall_clock(Resolution::Millis, 0, &seconds, Resolution::Seconds, 1);
+    /// assert_eq!(ordering, Ordering::Greater);
+    /// ```
+    pub fn compare_wall_clock(
+        &self,
+        self_resolution: Resolution,
+        self_offset: i64,
+        other: &FID,
+        other_resolution: Resolution,
+        other_offset: i64,
+    ) -> cmp::Ordering {
+        let unix_nanos = |fid: &FID, resolution: Resolution, offset: i64| {
+            fid.timestamp_duration(resolution).as_nanos() as i128 - offset as i128 * 1_000_000_000
+        };
+        unix_nanos(self, self_resolution, self_offset).cmp(&unix_nanos(
+            other,
+            other_resolution,
+            other_offset,
+        ))
+    }
+
+    /// Guess resolution of generator which produced this FID
+    ///
+    /// Coarsest resolution which puts timestamp (with offset_secs) between
+    /// years 1970 and 2200 is chosen, `None` if no resolution does.
+    /// Heuristic only: during first couple of months after the epoch
+    /// (~67 days for default one) millisecond ids look like seconds ones and
+    /// microsecond ids look like millisecond ones, store resolution alongside
+    /// ids whenever possible
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::{FID, Resolution};
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(fid.plausible_resolution(-1483228800), Some(Resolution::Millis));
+    /// let fid = FID::new(0x204dc595637 / 1000, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(fid.plausible_resolution(-1483228800), Some(Resolution::Seconds));
+    /// ```
+    pub fn plausible_resolution(&self, offset_secs: i64) -> Option<Resolution> {
+        // 2200-01-01T00:00:00Z
+        const UNIX_MAX: i128 = 7_258_118_400;
+        [Resolution::Seconds, Resolution::Millis, Resolution::Micros]
+            .iter()
+            .cloned()
+            .find(|x| {
+                let unix = (self.timestamp() / x.per_second()) as i128 - offset_secs as i128;
+                (0..UNIX_MAX).contains(&unix)
+            })
+    }
+
+    /// Human-readable string with decoded timestamp, sequence and generator
+    ///
+    /// offset_secs and in_seconds must match generator settings
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// let fid = FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(
+    ///     fid.to_debug_string(-1483228800, false),
+    ///     "QJuLKsbysSw@2087-05-07T06:52:47.031Z#seq1196#gen300"
+    /// );
+    /// ```
+    pub fn to_debug_string(&self, offset_secs: i64, in_seconds: bool) -> String {
+        let (timestamp, sequence, generator) = self.components();
+        let millis = if in_seconds {
+            timestamp as i128 * 1000
+        } else {
+            timestamp as i128
+        } - offset_secs as i128 * 1000;
+        let secs = millis.div_euclid(1000) as i64;
+        let (year, month, day) = civil_from_days(secs.div_euclid(86400));
+        let secs = secs.rem_euclid(86400);
+        let mut time = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        );
+        if !in_seconds {
+            time += &format!(".{:03}", millis.rem_euclid(1000));
+        }
+        format!(
+            "{}@{}Z#seq{}#gen{}",
+            self.to_string(),
+            time,
+            sequence,
+            generator
+        )
+    }
+}
+
+/// days since 1970-01-01 to (year, month, day)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Moment of zero timestamp for given offset
+#[cfg(feature = "std")]
+fn offset_epoch(offset_secs: i64) -> Option<SystemTime> {
+    let offset = time::Duration::from_secs(offset_secs.unsigned_abs());
+    if offset_secs < 0 {
+        UNIX_EPOCH.checked_add(offset)
+    } else {
+        UNIX_EPOCH.checked_sub(offset)
+    }
+}
+
+impl Deref for B64Str {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        // constructed from `to_b64` only, base64 alphabet is ASCII
+        unsafe { core::str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl AsRef<str> for B64Str {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Display for B64Str {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let v = self.to_b64();
-        let s = String::from_utf8_lossy(&v);
-        write!(f, "{}", s)
+        f.pad(self)
     }
 }
 
+impl PartialEq<str> for B64Str {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for B64Str {
+    fn eq(&self, other: &&'a str) -> bool {
+        **self == **other
+    }
+}
+
+impl fmt::Debug for B64Str {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl fmt::Debug for FID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "FID{{ id: \"{}\"; ts: {}; seq: {}; gen: {} }}",
+            self,
+            self.timestamp(),
+            self.sequence(),
+            self.generator()
+        )
+    }
+}
+
+impl fmt::Display for FID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.to_b64_str())
+    }
+}
+
+/// Raw conversion, all 64 bits are kept
+///
+/// Reserved high bit (`config::PARITY_MASK`) is not described by getters but
+/// survives `to_bytes`, `to_b64` and `Into<u64>` round-trips, so parity set by
+/// `FID::with_parity` is preserved. Use `FID::verify_parity` or check
+/// `PARITY_MASK` to detect a corrupted high bit.
 impl From<u64> for FID {
     fn from(id: u64) -> FID {
         FID(id)
@@ -262,6 +1377,46 @@ impl From<FID> for u64 {
     }
 }
 
+/// Comparison with raw `u64` value, alike `u64::from(fid) == x`
+impl PartialEq<u64> for FID {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+impl PartialEq<FID> for u64 {
+    fn eq(&self, other: &FID) -> bool {
+        *self == other.0
+    }
+}
+impl PartialOrd<u64> for FID {
+    fn partial_cmp(&self, other: &u64) -> Option<cmp::Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+impl PartialOrd<FID> for u64 {
+    fn partial_cmp(&self, other: &FID) -> Option<cmp::Ordering> {
+        self.partial_cmp(&other.0)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for FID {
+    type Error = Error;
+
+    /// alike `FID::from_slice(val)`
+    fn try_from(val: &'a [u8]) -> Result<FID> {
+        FID::from_slice(val)
+    }
+}
+
+impl TryFrom<[u8; 8]> for FID {
+    type Error = Error;
+
+    /// alike `FID::from_bytes(&val)`, never fails
+    fn try_from(val: [u8; 8]) -> Result<FID> {
+        Ok(FID::from_bytes(&val))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -275,6 +1430,7 @@ mod test {
         assert_eq!(fid.timestamp(), timestamp);
         assert_eq!(fid.sequence(), sequence);
         assert_eq!(fid.generator(), generator);
+        assert_eq!(fid.components(), (timestamp, sequence, generator));
         assert_eq!(
             FID::new(1 << cfg::TIMESTAMP_LENGTH, 0, 0).unwrap_err(),
             Error::TimestampOverflow(1 << cfg::TIMESTAMP_LENGTH)
@@ -287,6 +1443,92 @@ mod test {
             FID::new(0, 0, 1 << cfg::GENERATOR_LENGTH).unwrap_err(),
             Error::GeneratorOverflow(1 << cfg::GENERATOR_LENGTH)
         );
+        assert_eq!(
+            FID::new(u64::MAX, u16::MAX, u16::MAX).unwrap_err(),
+            Error::TimestampOverflow(u64::MAX)
+        );
+        let max = FID::new(cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX).unwrap();
+        assert_eq!(max.components(), (cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX));
+    }
+
+    #[test]
+    fn checked() {
+        use config::test_constants::*;
+        assert_eq!(
+            FID::checked(TIMESTAMP, SEQUENCE, GENERATOR),
+            Some(FID::new(TIMESTAMP, SEQUENCE, GENERATOR).unwrap())
+        );
+        assert_eq!(FID::checked(1 << cfg::TIMESTAMP_LENGTH, SEQUENCE, GENERATOR), None);
+        assert_eq!(FID::checked(TIMESTAMP, 1 << cfg::SEQUENCE_LENGTH, GENERATOR), None);
+        assert_eq!(FID::checked(TIMESTAMP, SEQUENCE, 1 << cfg::GENERATOR_LENGTH), None);
+    }
+
+    #[test]
+    fn resolution() {
+        use config::test_constants::*;
+        let duration = time::Duration::new(TIMESTAMP, 123_456_789);
+        assert_eq!(Resolution::Seconds.units(duration), TIMESTAMP);
+        assert_eq!(Resolution::Millis.units(duration), TIMESTAMP * 1000 + 123);
+        assert_eq!(Resolution::Micros.units(duration), TIMESTAMP * 1_000_000 + 123_456);
+        for x in [Resolution::Seconds, Resolution::Millis, Resolution::Micros].iter() {
+            assert_eq!(x.duration(x.per_second()), time::Duration::from_secs(1));
+            assert_eq!(x.units(x.duration(TIMESTAMP)), TIMESTAMP);
+        }
+
+        let offset = cfg::gbuilder_defaults::TIMESTAMP_OFFSET;
+        let fid = FID::new(TIMESTAMP, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(fid.plausible_resolution(offset), Some(Resolution::Millis));
+        let fid = FID::new(TIMESTAMP / 1000, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(fid.plausible_resolution(offset), Some(Resolution::Seconds));
+        let fid = FID::new(cfg::TIMESTAMP_MAX, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(fid.plausible_resolution(offset), Some(Resolution::Millis));
+        assert_eq!(fid.plausible_resolution(-5_000_000_000), Some(Resolution::Micros));
+        assert_eq!(fid.plausible_resolution(i64::MIN), None);
+
+        // epoch in 1906, ids of 2017
+        let offset = 2_000_000_000;
+        let fid = FID::new(3_500_000_000_000, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(fid.plausible_resolution(offset), Some(Resolution::Millis));
+        let fid = FID::new(3_500_000_000, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(fid.plausible_resolution(offset), Some(Resolution::Seconds));
+        // before 1970 in any resolution
+        let fid = FID::new(1_000, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(fid.plausible_resolution(offset), None);
+        assert_eq!(fid.plausible_resolution(i64::MAX), None);
+    }
+
+    #[test]
+    fn compose() {
+        use config::test_constants::*;
+        let raw = u64::from(FID::from_bytes(BIN));
+        assert_eq!(super::compose(TIMESTAMP, SEQUENCE, GENERATOR).unwrap(), raw);
+        assert_eq!(super::decompose(raw), (TIMESTAMP, SEQUENCE, GENERATOR));
+        assert_eq!(super::decompose(raw | cfg::PARITY_MASK), (TIMESTAMP, SEQUENCE, GENERATOR));
+        assert_eq!(
+            super::compose(TIMESTAMP, 1 << cfg::SEQUENCE_LENGTH, GENERATOR).unwrap_err(),
+            Error::SequenceOverflow(1 << cfg::SEQUENCE_LENGTH)
+        );
+    }
+
+    #[test]
+    fn new_const() {
+        const TIMESTAMP: u64 = cfg::test_constants::TIMESTAMP;
+        const SEQUENCE: u16 = cfg::test_constants::SEQUENCE;
+        const GENERATOR: u16 = cfg::test_constants::GENERATOR;
+        const FID_CONST: FID = FID::new_const(TIMESTAMP, SEQUENCE, GENERATOR);
+        assert_eq!(&&FID_CONST.to_bytes(), &cfg::test_constants::BIN);
+        let fid = FID::new_const(1 << cfg::TIMESTAMP_LENGTH, 1 << cfg::SEQUENCE_LENGTH, 0);
+        assert_eq!(u64::from(fid), 0);
+    }
+
+    #[test]
+    fn nil() {
+        assert!(FID::nil().is_nil());
+        assert_eq!(FID::nil(), FID::from(0));
+        assert_eq!(FID::nil().to_bytes(), [0u8; 8]);
+        assert!(!FID::new(0, 0, 1).unwrap().is_nil());
+        assert!(!FID::new(0, 1, 0).unwrap().is_nil());
+        assert!(!FID::from_b64(cfg::test_constants::B64).unwrap().is_nil());
     }
 
     #[test]
@@ -301,6 +1543,39 @@ mod test {
         assert_eq!(&&bytes, &cfg::test_constants::BIN);
         assert_eq!(fid, fid_from_bytes);
         assert_eq!(fid, fid_from_slice);
+
+        let mut bytes_le = fid.to_bytes_le();
+        assert_eq!(FID::from_bytes_le(&bytes_le), fid);
+        assert_eq!(FID::from_slice_le(&bytes_le).unwrap(), fid);
+        bytes_le.reverse();
+        assert_eq!(&&bytes_le, &cfg::test_constants::BIN);
+        assert_eq!(
+            FID::from_slice_le(&bytes_le[1..]).unwrap_err(),
+            Error::WrongSliceSize(7)
+        );
+
+        let mut buffer = [0xffu8; 9];
+        fid.write_bytes(&mut buffer).unwrap();
+        assert_eq!(&buffer[..8], &cfg::test_constants::BIN[..]);
+        assert_eq!(buffer[8], 0xff);
+        assert_eq!(
+            fid.write_bytes(&mut buffer[..7]).unwrap_err(),
+            Error::WrongSliceSize(7)
+        );
+    }
+
+    #[test]
+    fn uuid_bytes() {
+        let fid = FID::from_bytes(cfg::test_constants::BIN);
+        let uuid = fid.to_uuid_bytes();
+        assert_eq!(&uuid[..8], &cfg::test_constants::BIN[..]);
+        assert_eq!(&uuid[8..], &[0u8; 8]);
+        assert_eq!(FID::from_uuid_bytes(&uuid).unwrap(), fid);
+        for i in 8..16 {
+            let mut uuid = uuid;
+            uuid[i] = 1;
+            assert_eq!(FID::from_uuid_bytes(&uuid).unwrap_err(), Error::InvalidUuid);
+        }
     }
 
     #[test]
@@ -311,6 +1586,419 @@ mod test {
         let fid = FID::new(timestamp, sequence, generator).unwrap();
         assert_eq!(&&fid.to_b64(), &cfg::test_constants::B64);
         assert_eq!(FID::from_b64(&fid.to_b64()).unwrap(), fid);
+        assert_eq!(FID::from_b64(b"Pm9rf79L4cw=").unwrap(), fid);
+        assert_eq!(
+            FID::from_b64(base64::urlsafe_encode(&fid.to_bytes()).as_slice()).unwrap(),
+            fid
+        );
+        for x in [&b""[..], b"Pm9rf79L4c", b"Pm9rf79L4cw==", b"Pm9rf79L4cwA"].iter() {
+            assert_eq!(FID::from_b64(x).unwrap_err(), Error::WrongSliceSize(x.len()));
+        }
+        for x in [&b"QJuL"[..], b"Pm9rf79L4cwAAAA"].iter() {
+            assert_eq!(FID::from_b64(x).unwrap_err(), Error::WrongSliceSize(x.len()));
+        }
+        assert_eq!(FID::from_b64(b"Pm9rf79L===").unwrap_err(), Error::WrongSliceSize(6));
+        assert_eq!(FID::from_b64(b"Pm9rf79L4==").unwrap_err(), Error::WrongSliceSize(6));
+        assert_eq!(FID::from_b64(b"Pm9rf79L4c==").unwrap_err(), Error::WrongSliceSize(7));
+
+        assert_eq!(FID::from_b64_exact(cfg::test_constants::B64).unwrap(), fid);
+        for x in [0u64, 1, u64::MAX, 0x5555_5555_5555_5555].iter() {
+            let fid = FID::from(*x);
+            assert_eq!(FID::from_b64_exact(&fid.to_b64()).unwrap(), fid);
+            assert_eq!(FID::from_b64_exact(&fid.to_b64()), FID::from_b64(&fid.to_b64()));
+        }
+        assert_eq!(
+            FID::from_b64_exact(b"Pm9rf79L4c!").unwrap_err(),
+            Error::Base64WrongSymbolError
+        );
+        assert_eq!(
+            FID::from_b64_exact(b"Pm9rf79L4c=").unwrap_err(),
+            Error::Base64WrongSymbolError
+        );
+    }
+
+    #[test]
+    fn is_valid_str() {
+        let b64 = String::from_utf8_lossy(cfg::test_constants::B64);
+        assert!(FID::is_valid_str(&b64));
+        assert!(FID::is_valid_str("--__09azAZ-"));
+        for x in ["", "Pm9rf79L4c", "Pm9rf79L4cw=", "Pm9rf79L4c=", "Pm9rf79L4c/", "Pm9rf79L4cлw"].iter() {
+            assert!(!FID::is_valid_str(x));
+        }
+        for x in 0..64u64 {
+            let fid = FID::from(x.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+            assert!(FID::is_valid_str(&fid.to_string()));
+        }
+    }
+
+    #[test]
+    fn b64_sortable() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        assert_eq!(FID::from_b64_sortable(&fid.to_b64_sortable()).unwrap(), fid);
+        let mut ids: Vec<_> = (0..256u64)
+            .map(|x| FID::from(x.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 1))
+            .collect();
+        let mut encoded: Vec<_> = ids.iter().map(|x| x.to_b64_sortable()).collect();
+        ids.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        encoded.sort();
+        for (x, y) in ids.iter().zip(encoded.iter()) {
+            assert_eq!(&x.to_b64_sortable(), y);
+        }
+        assert_eq!(
+            FID::from_b64_sortable(&fid.to_b64_sortable()[1..]).unwrap_err(),
+            Error::WrongSliceSize(10)
+        );
+        assert_eq!(
+            FID::from_b64_sortable(b"F8iA9gQmg+k").unwrap_err(),
+            Error::Base64WrongSymbolError
+        );
+        assert_eq!(
+            FID::from_b64_sortable(b"F8iA9gQmg==").unwrap_err(),
+            Error::WrongSliceSize(6)
+        );
+    }
+
+    #[test]
+    fn parse_any() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let raw = u64::from(fid.clone());
+        assert_eq!(FID::parse_any(&format!("{}", raw)).unwrap(), fid);
+        assert_eq!(FID::parse_any(&format!("0x{:x}", raw)).unwrap(), fid);
+        assert_eq!(FID::parse_any("12345678901").unwrap(), FID::from_string("12345678901").unwrap());
+        assert_eq!(FID::parse_any("1234567890").unwrap(), FID::from(1234567890));
+        assert_eq!(FID::parse_any("0x").unwrap_err(), Error::IntegerParseError);
+        assert_eq!(FID::parse_any("0xfoo").unwrap_err(), Error::IntegerParseError);
+        assert_eq!(
+            FID::parse_any("99999999999999999999").unwrap_err(),
+            Error::IntegerParseError
+        );
+        assert_eq!(FID::parse_any("").unwrap_err(), Error::WrongSliceSize(0));
+    }
+
+    #[test]
+    fn next_value() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        assert!(fid.next_value().unwrap() > fid);
+        assert_eq!(fid.next_value().unwrap().prev_value().unwrap(), fid);
+        let max = FID::new(cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX).unwrap();
+        assert_eq!(max.next_value(), None);
+        assert_eq!(max.prev_value().unwrap().next_value().unwrap(), max);
+        assert_eq!(FID::nil().prev_value(), None);
+        assert_eq!(FID::nil().next_value().unwrap().components(), (0, 0, 1));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_ids() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let data = format!("{}\r\n\n \t\n\t{} \nbad\n", fid, fid);
+        let ids: Vec<Result<FID>> = super::read_ids(data.as_bytes()).collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], Ok(fid.clone()));
+        assert_eq!(ids[1], Ok(fid));
+        assert!(ids[2].is_err());
+        let ids: Vec<Result<FID>> = super::read_ids(&[0xffu8, b'\n'][..]).collect();
+        assert_eq!(ids, vec![Err(Error::IoError)]);
+        assert_eq!(super::read_ids(&b""[..]).count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_ids() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let ids = vec![fid.clone(), FID::nil(), fid.next_value().unwrap()];
+        let mut out = Vec::new();
+        super::write_ids(ids.clone(), &mut out).unwrap();
+        assert_eq!(out.len(), ids.len() * 12);
+        assert_eq!(&out[..12], &b"Pm9rf79L4cw\n"[..]);
+        let back: Vec<FID> = super::read_ids(&out[..]).map(|x| x.unwrap()).collect();
+        assert_eq!(back, ids);
+        let mut out = Vec::new();
+        super::write_ids(Vec::new(), &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn timestamp_duration() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let ts = cfg::test_constants::TIMESTAMP;
+        let micros = fid.timestamp_duration(Resolution::Micros);
+        assert_eq!(micros, time::Duration::new(ts / 1_000_000, (ts % 1_000_000) as u32 * 1000));
+        let millis = fid.timestamp_duration(Resolution::Millis);
+        assert_eq!(millis, time::Duration::new(ts / 1000, (ts % 1000) as u32 * 1_000_000));
+        assert_eq!(fid.timestamp_duration(Resolution::Seconds), time::Duration::from_secs(ts));
+        assert_eq!(FID::nil().timestamp_duration(Resolution::Millis), time::Duration::new(0, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn system_time() {
+        use config::test_constants::*;
+        let offset = cfg::gbuilder_defaults::TIMESTAMP_OFFSET;
+        let fid = FID::new(TIMESTAMP, SEQUENCE, GENERATOR).unwrap();
+        let time = fid.to_system_time(offset, Resolution::Millis).unwrap();
+        let since_unix = time.duration_since(UNIX_EPOCH).unwrap();
+        assert_eq!(since_unix.as_secs() as i64, TIMESTAMP as i64 / 1000 - offset);
+        let lo = FID::from_system_time(time, offset, Resolution::Millis).unwrap();
+        assert_eq!(lo, FID::range_for_timestamp(TIMESTAMP).unwrap().0);
+        let secs = FID::from_system_time(time, offset, Resolution::Seconds).unwrap();
+        assert_eq!(secs.timestamp(), TIMESTAMP / 1000);
+        let truncated = time - time::Duration::from_millis(TIMESTAMP % 1000);
+        assert_eq!(secs.to_system_time(offset, Resolution::Seconds), Some(truncated));
+
+        let before = offset_epoch(offset).unwrap() - time::Duration::from_millis(1);
+        let err = FID::from_system_time(before, offset, Resolution::Millis).unwrap_err();
+        assert_eq!(err, Error::SysTimeIsInPast);
+        let err = FID::from_system_time(time, offset, Resolution::Micros).unwrap_err();
+        assert_eq!(err, Error::TimestampOverflow(TIMESTAMP * 1000));
+
+        assert_eq!(FID::nil().to_system_time(i64::MIN, Resolution::Millis), None);
+        assert_eq!(fid.to_system_time(i64::MIN + 1, Resolution::Millis), None);
+        let err = FID::from_system_time(time, i64::MAX, Resolution::Millis).unwrap_err();
+        assert_eq!(err, Error::TimestampOverflow(u64::MAX));
+        let err = FID::from_system_time(time, i64::MIN, Resolution::Micros).unwrap_err();
+        assert_eq!(err, Error::SysTimeIsInPast);
+        let far = UNIX_EPOCH + time::Duration::from_secs(1 << 60);
+        let err = FID::from_system_time(far, 0, Resolution::Micros).unwrap_err();
+        assert_eq!(err, Error::TimestampOverflow(u64::MAX));
+    }
+
+    #[test]
+    fn to_b64_into() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let mut buf = [0u8; 22];
+        assert_eq!(fid.to_b64_into(&mut buf[..11]), Ok(11));
+        assert_eq!(FID::nil().to_b64_into(&mut buf[11..]), Ok(11));
+        assert_eq!(&buf[..11], cfg::test_constants::B64);
+        assert_eq!(&buf[11..], &FID::nil().to_b64());
+        let mut short = [0u8; 10];
+        assert_eq!(fid.to_b64_into(&mut short), Err(Error::Base64BufferTooSmall));
+        assert_eq!(short, [0u8; 10]);
+    }
+
+    #[test]
+    fn compare_wall_clock() {
+        use config::test_constants::*;
+        let offset = cfg::gbuilder_defaults::TIMESTAMP_OFFSET;
+        let millis = FID::new(TIMESTAMP, SEQUENCE, GENERATOR).unwrap();
+        let seconds = FID::new(TIMESTAMP / 1000, cfg::SEQUENCE_MAX, 0).unwrap();
+        let cmp = |a: &FID, ar, ao, b: &FID, br, bo| a.compare_wall_clock(ar, ao, b, br, bo);
+        let (ms, s) = (Resolution::Millis, Resolution::Seconds);
+        assert_eq!(cmp(&millis, ms, offset, &seconds, s, offset), cmp::Ordering::Greater);
+        assert_eq!(cmp(&seconds, s, offset, &millis, ms, offset), cmp::Ordering::Less);
+        assert_eq!(cmp(&seconds, s, offset - 1, &millis, ms, offset), cmp::Ordering::Greater);
+        assert_eq!(cmp(&millis, ms, offset, &millis, ms, offset), cmp::Ordering::Equal);
+        let micros = FID::new(1_500_000, 0, 0).unwrap();
+        let short = FID::new(1_500, 0, 0).unwrap();
+        assert_eq!(cmp(&micros, Resolution::Micros, 0, &short, ms, 0), cmp::Ordering::Equal);
+        assert_eq!(cmp(&micros, Resolution::Micros, 0, &short, s, 0), cmp::Ordering::Less);
+        let later = millis.next_value().unwrap();
+        assert_eq!(cmp(&later, ms, offset, &millis, ms, offset), cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn masked_generator_range() {
+        let (lo, hi) = FID::masked_generator_range(4, 16).unwrap();
+        let ids: Vec<FID> = (0..=cfg::GENERATOR_MAX)
+            .map(|x| FID::new(cfg::test_constants::TIMESTAMP, cfg::SEQUENCE_MAX, x).unwrap())
+            .filter(|x| lo <= x.0 & ::GENERATOR_MASK && x.0 & ::GENERATOR_MASK <= hi)
+            .collect();
+        assert_eq!(ids.len(), 13);
+        assert!(ids.iter().enumerate().all(|(i, x)| x.generator() == i as u16 + 4));
+        let (lo, hi) = FID::masked_generator_range(0, cfg::GENERATOR_MAX).unwrap();
+        assert_eq!((lo, hi), (0, ::GENERATOR_MASK));
+        assert_eq!(
+            FID::masked_generator_range(0, cfg::GENERATOR_MAX + 1).unwrap_err(),
+            Error::GeneratorOverflow(cfg::GENERATOR_MAX + 1)
+        );
+    }
+
+    #[test]
+    fn generator_high() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let high = fid.to_generator_high();
+        assert_eq!(high.components(), fid.components());
+        assert_eq!(high.to_fid(), fid);
+        assert_ne!(u64::from(high), u64::from(fid.clone()));
+        let sealed = fid.with_parity().to_generator_high();
+        assert!(sealed.verify_parity());
+        assert_eq!(sealed.to_fid(), fid.with_parity());
+        assert_eq!(u64::from(FID::nil().to_generator_high()), 0);
+    }
+
+    #[test]
+    fn version() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        assert_eq!(fid.version(), 0);
+        let tagged = fid.with_version(1).unwrap();
+        assert_eq!(tagged.version(), 1);
+        assert_eq!(tagged.components(), fid.components());
+        assert_eq!(tagged.without_generator(), fid.without_generator());
+        assert_eq!(tagged.with_version(0).unwrap(), fid);
+        assert_eq!(fid.with_version(0).unwrap(), fid);
+        assert_eq!(fid.with_version(2).unwrap_err(), Error::InvalidVersion(2));
+    }
+
+    #[test]
+    fn from_hash() {
+        let max = FID::new(cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX).unwrap();
+        assert_eq!(FID::from_hash(u64::MAX), max);
+        assert_eq!(FID::from_hash(0), FID::nil());
+        assert_eq!(FID::from_hash(cfg::PARITY_MASK), FID::nil());
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        assert_eq!(FID::from_hash(fid.0), fid);
+        assert_eq!(FID::from_hash(fid.0 | cfg::PARITY_MASK), fid);
+        assert_eq!(FID::from_hash(fid.0).version(), 0);
+    }
+
+    #[test]
+    fn display() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        assert_eq!(format!("{}", fid.display()), format!("{}", fid));
+        assert_eq!(format!("{:<12}|", fid.display()), format!("{:<12}|", fid));
+        assert_eq!(format!("{:.4}", fid.display()), "Pm9r");
+        assert_eq!(format!("{}", FID::nil().display()), "AAAAAAAAAAA");
+    }
+
+    #[test]
+    fn range() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let ids: Vec<FID> = super::range(fid.clone(), fid.checked_add_raw(5).unwrap()).collect();
+        assert_eq!(ids.len(), 5);
+        assert!(ids.windows(2).all(|x| x[0].next_value().unwrap() == x[1]));
+        assert_eq!(ids[0], fid);
+        assert_eq!(super::range(fid.clone(), fid.clone()).count(), 0);
+        assert_eq!(super::range(fid.next_value().unwrap(), fid.clone()).count(), 0);
+        let max = FID::new(cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX).unwrap();
+        let ids: Vec<FID> = super::range(max.prev_value().unwrap(), FID::from(u64::MAX)).collect();
+        assert_eq!(ids, vec![max.prev_value().unwrap(), max.clone()]);
+        assert_eq!(super::range(FID::from(u64::MAX), FID::from(u64::MAX)).count(), 0);
+        assert_eq!(super::range(FID::from(cfg::PARITY_MASK), max).count(), 0);
+    }
+
+    #[test]
+    fn from_b64_canonical() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        assert_eq!(FID::from_b64_canonical(cfg::test_constants::B64).unwrap(), fid);
+        assert_eq!(FID::from_b64_canonical(b"Pm9rf79L4cw=").unwrap(), fid);
+        for x in [&b"Pm9rf79L4cx"[..], b"Pm9rf79L4cz", b"Pm9rf79L4cz="].iter() {
+            assert_eq!(FID::from_b64(x).unwrap(), fid);
+            assert_eq!(FID::from_b64_canonical(x).unwrap_err(), Error::Base64NonCanonical);
+        }
+        let std = FID::from(u64::MAX >> 1);
+        let b64 = std.to_b64();
+        assert_eq!(&b64, b"f_________8");
+        assert_eq!(FID::from_b64(b"f/////////8").unwrap(), std);
+        assert_eq!(FID::from_b64_canonical(b"f/////////8").unwrap_err(), Error::Base64NonCanonical);
+        assert_eq!(FID::from_b64_canonical(b"QJuL").unwrap_err(), Error::WrongSliceSize(4));
+    }
+
+    #[test]
+    fn add_raw() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let max = FID::new(cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX).unwrap();
+        assert_eq!(fid.checked_add_raw(0).unwrap(), fid);
+        assert_eq!(fid.checked_add_raw(1), fid.next_value());
+        assert_eq!(fid.saturating_add_raw(1), fid.next_value().unwrap());
+        assert_eq!(max.checked_add_raw(0).unwrap(), max);
+        assert_eq!(max.checked_add_raw(1), None);
+        assert_eq!(max.saturating_add_raw(1), max);
+        assert_eq!(FID::nil().checked_add_raw(u64::from(max.clone())).unwrap(), max);
+        let step: u64 = 1 << cfg::TIMESTAMP_SHIFT;
+        let next = fid.checked_add_raw(step).unwrap();
+        assert_eq!(next.timestamp(), fid.timestamp() + 1);
+        assert_eq!((next.sequence(), next.generator()), (fid.sequence(), fid.generator()));
+    }
+
+    #[test]
+    fn parity() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let sealed = fid.with_parity();
+        assert!(sealed.verify_parity());
+        assert_eq!(sealed.with_parity(), sealed);
+        assert_eq!(sealed.components(), fid.components());
+        assert_eq!(sealed.without_generator(), fid.without_generator());
+        for i in 0..64 {
+            assert!(!FID::from(u64::from(sealed.clone()) ^ 1 << i).verify_parity());
+        }
+        assert!(FID::nil().verify_parity());
+        assert_eq!(FID::new(0, 0, 1).unwrap().with_parity().components(), (0, 0, 1));
+        assert_eq!(u64::from(FID::new(0, 0, 1).unwrap().with_parity()), 1 << 63 | 1);
+    }
+
+    #[test]
+    fn sequences_for() {
+        use config::test_constants::*;
+        let ids: Vec<FID> = FID::sequences_for(TIMESTAMP, GENERATOR).unwrap().collect();
+        assert_eq!(ids.len(), 1 << cfg::SEQUENCE_LENGTH);
+        for (i, x) in ids.iter().enumerate() {
+            assert_eq!(x.components(), (TIMESTAMP, i as u16, GENERATOR));
+        }
+        assert!(ids.windows(2).all(|x| x[0] < x[1]));
+        assert!(FID::sequences_for(1 << cfg::TIMESTAMP_LENGTH, GENERATOR).is_err());
+        assert!(FID::sequences_for(TIMESTAMP, 1 << cfg::GENERATOR_LENGTH).is_err());
+    }
+
+    #[test]
+    fn range_for_timestamp() {
+        let timestamp = cfg::test_constants::TIMESTAMP;
+        let (lo, hi) = FID::range_for_timestamp(timestamp).unwrap();
+        assert_eq!(lo.components(), (timestamp, 0, 0));
+        assert_eq!(
+            hi.components(),
+            (timestamp, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX)
+        );
+        assert!(FID::new(timestamp - 1, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX).unwrap() < lo);
+        assert!(FID::new(timestamp + 1, 0, 0).unwrap() > hi);
+        assert_eq!(
+            FID::range_for_timestamp(1 << cfg::TIMESTAMP_LENGTH).unwrap_err(),
+            Error::TimestampOverflow(1 << cfg::TIMESTAMP_LENGTH)
+        );
+    }
+
+    #[test]
+    fn to_debug_string() {
+        use config::test_constants::*;
+        let fid = FID::new(0, 1, 2).unwrap();
+        assert_eq!(
+            fid.to_debug_string(cfg::gbuilder_defaults::TIMESTAMP_OFFSET, false),
+            format!("{}@2017-01-01T00:00:00.000Z#seq1#gen2", fid)
+        );
+        assert_eq!(
+            fid.to_debug_string(0, true),
+            format!("{}@1970-01-01T00:00:00Z#seq1#gen2", fid)
+        );
+        assert_eq!(
+            fid.to_debug_string(86400, true),
+            format!("{}@1969-12-31T00:00:00Z#seq1#gen2", fid)
+        );
+        let fid = FID::new(951782400 + 86400 + 3661, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(
+            fid.to_debug_string(0, true),
+            format!("{}@2000-03-01T01:01:01Z#seq{}#gen{}", fid, SEQUENCE, GENERATOR)
+        );
+        let fid = FID::new(TIMESTAMP, SEQUENCE, GENERATOR).unwrap();
+        assert_eq!(
+            fid.to_debug_string(cfg::gbuilder_defaults::TIMESTAMP_OFFSET, false),
+            format!("{}@2084-12-24T09:05:07.066Z#seq{}#gen{}", fid, SEQUENCE, GENERATOR)
+        );
+    }
+
+    #[test]
+    fn to_b64_str() {
+        let fid = FID::new(
+            cfg::test_constants::TIMESTAMP,
+            cfg::test_constants::SEQUENCE,
+            cfg::test_constants::GENERATOR,
+        ).unwrap();
+        let b64 = fid.to_b64_str();
+        assert_eq!(b64.as_bytes(), &cfg::test_constants::B64[..]);
+        assert_eq!(b64.as_ref(), fid.to_string());
+        assert_eq!(format!("{}", b64), format!("{}", fid));
+        assert_eq!(format!("{:?}", b64), format!("{:?}", fid.to_string()));
+        assert_eq!(b64, "Pm9rf79L4cw");
+        assert_eq!(b64, *"Pm9rf79L4cw");
+        assert_ne!(b64, "Pm9rf79L4cw=");
     }
 
     #[test]
@@ -344,5 +2032,59 @@ mod test {
         let from_fid: u64 = From::from(fid.clone());
         let from_64: FID = From::from(from_fid);
         assert_eq!(from_64, fid);
+
+        let raw = from_fid | cfg::PARITY_MASK;
+        let high = FID::from(raw);
+        assert_eq!(high.components(), fid.components());
+        assert_eq!(u64::from(high.clone()), raw);
+        assert_eq!(FID::from_bytes(&high.to_bytes()), high);
+        assert_eq!(FID::from_b64(&high.to_b64()).unwrap(), high);
+        assert_ne!(high, fid);
+    }
+
+    #[test]
+    fn cmp_u64() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let raw = u64::from(fid.clone());
+        assert!(fid == raw);
+        assert!(raw == fid);
+        assert!(fid != raw + 1);
+        assert!(fid < raw + 1);
+        assert!(raw + 1 > fid);
+        assert!(fid > raw - 1);
+        assert!(raw - 1 < fid);
+        assert!(fid <= raw);
+    }
+
+    #[test]
+    fn try_from() {
+        let fid = FID::try_from(&cfg::test_constants::BIN[..]).unwrap();
+        assert_eq!(&&fid.to_bytes(), &cfg::test_constants::BIN);
+        assert_eq!(FID::try_from(*cfg::test_constants::BIN).unwrap(), fid);
+        assert_eq!(
+            FID::try_from(&cfg::test_constants::BIN[1..]).unwrap_err(),
+            Error::WrongSliceSize(7)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn with_b64_cached() {
+        let first = FID::new(cfg::test_constants::TIMESTAMP, 0, 0).unwrap();
+        let second = FID::new(
+            cfg::test_constants::TIMESTAMP,
+            cfg::test_constants::SEQUENCE,
+            cfg::test_constants::GENERATOR,
+        ).unwrap();
+        first.with_b64_cached(|s| assert_eq!(s, first.to_string()));
+        second.with_b64_cached(|s| assert_eq!(s.as_bytes(), cfg::test_constants::B64));
+        let nested = first.with_b64_cached(|a| {
+            second.with_b64_cached(|b| {
+                assert_eq!(a, first.to_string());
+                assert_eq!(b, second.to_string());
+                format!("{} {}", a, b)
+            })
+        });
+        assert_eq!(nested, format!("{} {}", first, second));
     }
 }
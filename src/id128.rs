@@ -0,0 +1,229 @@
+//! Extended 128-bit flower identificator
+//!
+//! 64-bit timestamp, 32-bit sequence and 32-bit generator by default
+//! (layout is set by `config::*128_LENGTH`),
+//! for large clusters and far-future timestamps. `FID` stays the default.
+
+use core::fmt;
+use alloc::string::String;
+use base64;
+use config as cfg;
+
+use {Error, Result};
+
+/// Extended flower identificator struct
+#[derive(PartialEq, PartialOrd, Clone)]
+pub struct FID128(u128);
+
+impl FID128 {
+    /// Create FID128 from components
+    ///
+    /// timestamp - `TIMESTAMP128_LENGTH` bits value
+    /// sequence  - `SEQUENCE128_LENGTH` bits value
+    /// generator - `GENERATOR128_LENGTH` bits value
+    ///
+    /// Bits above field length are dropped, see `FID128Generator` for checked values
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id128::FID128;
+    /// let fid = FID128::new(0x204dc595637, 0x4ac, 0x12c);
+    /// assert_eq!(fid.components(), (0x204dc595637, 0x4ac, 0x12c));
+    /// ```
+    pub fn new(timestamp: u64, sequence: u32, generator: u32) -> FID128 {
+        FID128(
+            (((timestamp & cfg::TIMESTAMP128_MAX) as u128)
+                << (cfg::GENERATOR128_LENGTH + cfg::SEQUENCE128_LENGTH))
+                | (((sequence & cfg::SEQUENCE128_MAX) as u128) << cfg::GENERATOR128_LENGTH)
+                | (generator & cfg::GENERATOR128_MAX) as u128,
+        )
+    }
+
+    /// Serialize FID128 (big-endian)
+    pub fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Deserialize FID128 (big-endian)
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id128::FID128;
+    /// let fid = FID128::new(0x204dc595637, 0x4ac, 0x12c);
+    /// assert_eq!(FID128::from_bytes(&fid.to_bytes()), fid);
+    /// ```
+    pub fn from_bytes(val: &[u8; 16]) -> FID128 {
+        FID128(u128::from_be_bytes(*val))
+    }
+
+    /// Deserialize FID128 (big-endian)
+    ///
+    /// # Failures
+    /// `Error::WrongSliceSize` if slice length != 16
+    pub fn from_slice(val: &[u8]) -> Result<FID128> {
+        if val.len() != 16 {
+            return Err(Error::WrongSliceSize(val.len()));
+        }
+        let mut tmp = [0u8; 16];
+        tmp.copy_from_slice(val);
+        Ok(FID128::from_bytes(&tmp))
+    }
+
+    /// Serialize FID128 to base64 (22 chars)
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id128::FID128;
+    /// let fid = FID128::new(0x204dc595637, 0x4ac, 0x12c);
+    /// assert_eq!(&fid.to_b64(), b"AAACBNxZVjcAAASsAAABLA");
+    /// ```
+    pub fn to_b64(&self) -> [u8; 22] {
+        let mut buffer = [0u8; 22];
+        base64::urlsafe_encode_without_pading_into(&self.to_bytes(), &mut buffer).unwrap();
+        buffer
+    }
+
+    /// Serialize FID128 to base64 string
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        // base64 alphabet is ASCII
+        String::from(unsafe { core::str::from_utf8_unchecked(&self.to_b64()) })
+    }
+
+    /// Deserialize FID128 from base64
+    ///
+    /// # Failures
+    /// `Error::WrongSliceSize` if input length isn't 22 (or 24 with trailing `==`)
+    /// or it doesn't decode to 16 bytes
+    /// `Error::Base64WrongSymbolError` decoding failed
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id128::FID128;
+    /// let fid = FID128::from_b64(b"AAACBNxZVjcAAASsAAABLA").unwrap();
+    /// assert_eq!(fid, FID128::new(0x204dc595637, 0x4ac, 0x12c));
+    /// ```
+    pub fn from_b64(val: &[u8]) -> Result<FID128> {
+        if !(val.len() == 22 || (val.len() == 24 && &val[22..] == b"==")) {
+            return Err(Error::WrongSliceSize(val.len()));
+        }
+        let mut buffer = [0u8; 16];
+        let len = base64::decode_into(val, Some(Error::Base64PaddingError), &mut buffer)?;
+        if len != buffer.len() {
+            return Err(Error::WrongSliceSize(len));
+        }
+        Ok(FID128::from_bytes(&buffer))
+    }
+
+    /// timestamp getter
+    pub fn timestamp(&self) -> u64 {
+        (self.0 >> (cfg::GENERATOR128_LENGTH + cfg::SEQUENCE128_LENGTH)) as u64
+            & cfg::TIMESTAMP128_MAX
+    }
+
+    /// sequence getter
+    pub fn sequence(&self) -> u32 {
+        (self.0 >> cfg::GENERATOR128_LENGTH) as u32 & cfg::SEQUENCE128_MAX
+    }
+
+    /// generator getter
+    pub fn generator(&self) -> u32 {
+        self.0 as u32 & cfg::GENERATOR128_MAX
+    }
+
+    /// (timestamp, sequence, generator) getter
+    pub fn components(&self) -> (u64, u32, u32) {
+        (self.timestamp(), self.sequence(), self.generator())
+    }
+}
+
+impl fmt::Debug for FID128 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "FID128{{ id: \"{}\"; ts: {}; seq: {}; gen: {} }}",
+            self,
+            self.timestamp(),
+            self.sequence(),
+            self.generator()
+        )
+    }
+}
+
+impl fmt::Display for FID128 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(&self.to_string())
+    }
+}
+
+impl From<u128> for FID128 {
+    fn from(id: u128) -> FID128 {
+        FID128(id)
+    }
+}
+
+impl From<FID128> for u128 {
+    fn from(id: FID128) -> u128 {
+        id.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn new_getters() {
+        let fid = FID128::new(u64::MAX, 0, u32::MAX);
+        assert_eq!(fid.components(), (u64::MAX, 0, u32::MAX));
+        let fid = FID128::new(0, u32::MAX, 0);
+        assert_eq!(fid.components(), (0, u32::MAX, 0));
+        assert!(FID128::new(1, 0, 0) > FID128::new(0, u32::MAX, u32::MAX));
+        assert!(FID128::new(1, 1, 0) > FID128::new(1, 0, u32::MAX));
+    }
+
+    #[test]
+    fn bytes() {
+        let fid = FID128::new(0x0102030405060708, 0x090a0b0c, 0x0d0e0f10);
+        let bytes = fid.to_bytes();
+        assert_eq!(&bytes[..], &(1..17).collect::<alloc::vec::Vec<u8>>()[..]);
+        assert_eq!(FID128::from_slice(&bytes).unwrap(), fid);
+        assert_eq!(
+            FID128::from_slice(&bytes[1..]).unwrap_err(),
+            Error::WrongSliceSize(15)
+        );
+    }
+
+    #[test]
+    fn base64() {
+        for x in [0u128, 1, u128::MAX, 0x5555 << 100].iter() {
+            let fid = FID128::from(*x);
+            assert_eq!(FID128::from_b64(&fid.to_b64()).unwrap(), fid);
+            assert_eq!(FID128::from_b64(fid.to_string().as_bytes()).unwrap(), fid);
+        }
+        let mut padded = FID128::from(1).to_b64().to_vec();
+        padded.extend_from_slice(b"==");
+        assert_eq!(FID128::from_b64(&padded).unwrap(), FID128::from(1));
+        assert_eq!(
+            FID128::from_b64(b"AAACBNxZVjcAAASsAAABL").unwrap_err(),
+            Error::WrongSliceSize(21)
+        );
+        assert_eq!(
+            FID128::from_b64(b"AAACBNxZVjcAAASsAAAB!A").unwrap_err(),
+            Error::Base64WrongSymbolError
+        );
+        let mut truncated = FID128::new(1, 2, 3).to_b64();
+        truncated[20..].copy_from_slice(b"==");
+        assert_eq!(FID128::from_b64(&truncated).unwrap_err(), Error::WrongSliceSize(15));
+    }
+
+    #[test]
+    fn fmt() {
+        let fid = FID128::new(0x204dc595637, 0x4ac, 0x12c);
+        assert_eq!(format!("{}", fid), "AAACBNxZVjcAAASsAAABLA");
+        assert_eq!(
+            format!("{:?}", fid),
+            "FID128{ id: \"AAACBNxZVjcAAASsAAABLA\"; ts: 2219899967031; seq: 1196; gen: 300 }"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Flower identificator in generator-high layout
+//!
+//! `| reserved | generator | sequence | timestamp |`, so numeric or
+//! lexicographic prefix of raw id selects generator (shard).
+//! See `FIDGenerator::next_generator_high`. `FID` stays the default.
+
+use core::fmt;
+use alloc::string::String;
+use config as cfg;
+use id::FID;
+
+use Result;
+
+const SEQUENCE_SHIFT: u64 = cfg::TIMESTAMP_LENGTH;
+const GENERATOR_SHIFT: u64 = SEQUENCE_SHIFT + cfg::SEQUENCE_LENGTH as u64;
+
+/// Flower identificator with generator in most-significant field
+///
+/// Getters decode generator-high layout. Reserved high bit is kept as is,
+/// parity (`FID::with_parity`) survives conversions both ways
+#[derive(PartialEq, PartialOrd, Clone)]
+pub struct GeneratorHighFID(u64);
+
+impl GeneratorHighFID {
+    /// Create GeneratorHighFID from components
+    ///
+    /// # Failures
+    /// alike `FID::new`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id_high::GeneratorHighFID;
+    /// let fid = GeneratorHighFID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(fid.components(), (0x204dc595637, 0x4ac, 0x12c));
+    /// assert_eq!(u64::from(fid) >> 53, 0x12c);
+    /// ```
+    pub fn new(timestamp: u64, sequence: u16, generator: u16) -> Result<GeneratorHighFID> {
+        FID::new(timestamp, sequence, generator).map(GeneratorHighFID::from)
+    }
+
+    /// Range of ids of one generator, alike `FID::masked_generator_range` for prefix routing
+    ///
+    /// # Failures
+    /// `Error::GeneratorOverflow` if generator is greater than `GENERATOR_MAX`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id_high::GeneratorHighFID;
+    /// let (lo, hi) = GeneratorHighFID::range_for_generator(0x12c).unwrap();
+    /// let fid = GeneratorHighFID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert!(lo <= fid && fid <= hi);
+    /// ```
+    pub fn range_for_generator(generator: u16) -> Result<(GeneratorHighFID, GeneratorHighFID)> {
+        let lo = GeneratorHighFID::new(0, 0, generator)?;
+        let hi = GeneratorHighFID::new(cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, generator)?;
+        Ok((lo, hi))
+    }
+
+    /// Convert to default layout
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id::FID;
+    /// use flowerid::id_high::GeneratorHighFID;
+    /// let fid = GeneratorHighFID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(fid.to_fid(), FID::new(0x204dc595637, 0x4ac, 0x12c).unwrap());
+    /// ```
+    pub fn to_fid(&self) -> FID {
+        let (timestamp, sequence, generator) = self.components();
+        let reserved = self.0 & cfg::PARITY_MASK;
+        FID::from(reserved | u64::from(FID::new(timestamp, sequence, generator).unwrap()))
+    }
+
+    /// Serialize GeneratorHighFID (big-endian)
+    pub fn to_bytes(&self) -> [u8; 8] {
+        self.0.to_be_bytes()
+    }
+
+    /// Deserialize GeneratorHighFID (big-endian)
+    pub fn from_bytes(val: &[u8; 8]) -> GeneratorHighFID {
+        GeneratorHighFID(u64::from_be_bytes(*val))
+    }
+
+    /// Serialize GeneratorHighFID to base64 (11 chars), alike `FID::to_b64`
+    pub fn to_b64(&self) -> [u8; 11] {
+        FID::from(self.0).to_b64()
+    }
+
+    /// Serialize GeneratorHighFID to base64 String
+    #[allow(clippy::inherent_to_string_shadow_display)]
+    pub fn to_string(&self) -> String {
+        FID::from(self.0).to_string()
+    }
+
+    /// Deserialize GeneratorHighFID from base64
+    ///
+    /// # Failures
+    /// alike `FID::from_b64`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::id_high::GeneratorHighFID;
+    /// let fid = GeneratorHighFID::new(0x204dc595637, 0x4ac, 0x12c).unwrap();
+    /// assert_eq!(GeneratorHighFID::from_b64(&fid.to_b64()).unwrap(), fid);
+    /// ```
+    pub fn from_b64(val: &[u8]) -> Result<GeneratorHighFID> {
+        FID::from_b64(val).map(|fid| GeneratorHighFID(u64::from(fid)))
+    }
+
+    /// Get timestamp
+    pub fn timestamp(&self) -> u64 {
+        self.0 & cfg::TIMESTAMP_MAX
+    }
+
+    /// Get sequence
+    pub fn sequence(&self) -> u16 {
+        (self.0 >> SEQUENCE_SHIFT) as u16 & cfg::SEQUENCE_MAX
+    }
+
+    /// Get generator
+    pub fn generator(&self) -> u16 {
+        (self.0 >> GENERATOR_SHIFT) as u16 & cfg::GENERATOR_MAX
+    }
+
+    /// (timestamp, sequence, generator)
+    pub fn components(&self) -> (u64, u16, u16) {
+        (self.timestamp(), self.sequence(), self.generator())
+    }
+
+    /// Check parity set by `FID::with_parity` or `FIDGeneratorBuilder::parity_bit`
+    ///
+    /// Parity doesn't depend on field order, alike `FID::verify_parity`
+    pub fn verify_parity(&self) -> bool {
+        FID::from(self.0).verify_parity()
+    }
+
+    /// Layout version tag from reserved high bit, alike `FID::version`
+    pub fn version(&self) -> u8 {
+        FID::from(self.0).version()
+    }
+}
+
+impl fmt::Debug for GeneratorHighFID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "GeneratorHighFID{{ id: \"{}\"; ts: {}; seq: {}; gen: {} }}",
+            self,
+            self.timestamp(),
+            self.sequence(),
+            self.generator()
+        )
+    }
+}
+
+impl fmt::Display for GeneratorHighFID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&FID::from(self.0), f)
+    }
+}
+
+/// Reorder fields of default layout FID, alike `FID::to_generator_high`
+impl From<FID> for GeneratorHighFID {
+    fn from(fid: FID) -> GeneratorHighFID {
+        let (timestamp, sequence, generator) = fid.components();
+        GeneratorHighFID(
+            (u64::from(fid) & cfg::PARITY_MASK)
+                | u64::from(generator) << GENERATOR_SHIFT
+                | u64::from(sequence) << SEQUENCE_SHIFT
+                | timestamp,
+        )
+    }
+}
+
+impl From<GeneratorHighFID> for FID {
+    fn from(fid: GeneratorHighFID) -> FID {
+        fid.to_fid()
+    }
+}
+
+/// Raw conversion, all 64 bits are kept
+impl From<u64> for GeneratorHighFID {
+    fn from(id: u64) -> GeneratorHighFID {
+        GeneratorHighFID(id)
+    }
+}
+
+impl From<GeneratorHighFID> for u64 {
+    fn from(id: GeneratorHighFID) -> u64 {
+        id.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use Error;
+
+    #[test]
+    fn layout() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let high = GeneratorHighFID::from(fid.clone());
+        assert_eq!(high.components(), fid.components());
+        assert_eq!(high.to_fid(), fid);
+        assert_eq!(high.0 >> 53, u64::from(cfg::test_constants::GENERATOR));
+        assert_eq!(high.0 & cfg::TIMESTAMP_MAX, cfg::test_constants::TIMESTAMP);
+        assert_eq!(GeneratorHighFID::from_bytes(&high.to_bytes()), high);
+        assert_eq!(GeneratorHighFID::from_b64(&high.to_b64()).unwrap(), high);
+        assert_eq!(high.to_string(), format!("{}", high));
+
+        let max = GeneratorHighFID::new(cfg::TIMESTAMP_MAX, cfg::SEQUENCE_MAX, cfg::GENERATOR_MAX);
+        assert_eq!(u64::from(max.unwrap()), !cfg::PARITY_MASK);
+        assert_eq!(
+            GeneratorHighFID::new(0, 0, cfg::GENERATOR_MAX + 1).unwrap_err(),
+            Error::GeneratorOverflow(cfg::GENERATOR_MAX + 1)
+        );
+        let a = GeneratorHighFID::new(cfg::TIMESTAMP_MAX, 0, 1).unwrap();
+        let b = GeneratorHighFID::new(0, 0, 2).unwrap();
+        assert!(a < b);
+        let (lo, hi) = GeneratorHighFID::range_for_generator(1).unwrap();
+        assert!(lo <= a && a <= hi && b > hi);
+    }
+
+    #[test]
+    fn reserved_bit() {
+        let fid = FID::from_b64(cfg::test_constants::B64).unwrap();
+        let sealed = GeneratorHighFID::from(fid.with_parity());
+        assert!(sealed.verify_parity());
+        assert_eq!(sealed.components(), fid.components());
+        assert_eq!(sealed.to_fid(), fid.with_parity());
+        let tagged = GeneratorHighFID::from(fid.with_version(1).unwrap());
+        assert_eq!(tagged.version(), 1);
+        assert_eq!(tagged.components(), fid.components());
+        assert_eq!(FID::from(tagged), fid.with_version(1).unwrap());
+    }
+}
@@ -10,17 +10,58 @@
 //! println!("{}", fid);
 //! println!("{:?}", fid);
 //! ```
+//!
+//! # no_std
+//! Disable default `std` feature to use `id`, `base64` and `config` modules
+//! with `core` and `alloc` only. Generators need system time and are
+//! available with `std` feature only.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate core;
+#[macro_use]
+extern crate alloc;
 
 pub mod id;
+pub mod id128;
+pub mod id_high;
+#[cfg(feature = "std")]
 pub mod generator;
+#[cfg(feature = "std")]
+pub mod generator128;
+#[cfg(feature = "std")]
+pub mod shared;
 pub mod base64;
 pub mod config;
+#[cfg(feature = "std")]
 mod stubs;
 
 pub use id::FID;
+pub use id128::FID128;
+pub use id_high::GeneratorHighFID;
+pub use config::{GENERATOR_MASK, SEQUENCE_MASK, TIMESTAMP_MASK};
+
+/// Length of FID base64 string
+///
+/// # Examples
+/// ```
+/// use flowerid::{FID, FID_STR_LEN};
+/// assert_eq!(FID::nil().to_b64().len(), FID_STR_LEN);
+/// ```
+pub const FID_STR_LEN: usize = 11;
+/// Length of FID binary representation
+///
+/// # Examples
+/// ```
+/// use flowerid::{FID, FID_BYTE_LEN};
+/// assert_eq!(FID::nil().to_bytes().len(), FID_BYTE_LEN);
+/// ```
+pub const FID_BYTE_LEN: usize = 8;
 
-use std::result;
-use std::fmt;
+use core::result;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::error;
 
 /// Errors
@@ -44,6 +85,35 @@ pub enum Error {
     Base64WrongSymbolError,
     /// (only ignore mode) combine Padding & WrongSymbol
     Base64PaddingWrongSymbolError,
+    /// integer string parsing failed
+    IntegerParseError,
+    /// timestamp offset combined with current time doesn't fit timestamp field
+    /// (computed timestamp, max timestamp)
+    InvalidOffset(i64, u64),
+    /// generation rate limit of current second is reached
+    RateLimited,
+    /// zero generation rate limit
+    InvalidRateLimit,
+    /// environment variable is missing or unparsable (variable name)
+    InvalidEnvVar(&'static str),
+    /// low 8 bytes of UUID representation are not zero
+    InvalidUuid,
+    /// reading input failed (read_ids)
+    IoError,
+    /// generator id is held by another `UniqueGenerator`
+    GeneratorInUse(u16),
+    /// layout version doesn't fit version tag bit
+    InvalidVersion(u8),
+    /// parity bit and version tag both claim reserved high bit
+    ReservedBitConflict,
+    /// system time is ahead of last timestamp more than allowed skew
+    SysTimeTooFarAhead,
+    /// base64 decodes but isn't canonical encoding of decoded bytes
+    Base64NonCanonical,
+    /// FID128 sequence overflow
+    Sequence128Overflow(u32),
+    /// FID128 generator overflow
+    Generator128Overflow(u32),
 }
 pub type Result<T> = result::Result<T, Error>;
 
@@ -53,6 +123,7 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {
     fn description(&self) -> &str {
         "base64 decoding error"
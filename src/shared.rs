@@ -0,0 +1,481 @@
+//! Flower identificator generator shared between threads
+use config as cfg;
+use generator::{FIDGenerator, FIDGeneratorBuilder};
+use id::FID;
+use std::cmp;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use {Error, Result};
+
+/// Generator which clones share one generator id
+///
+/// All clones refer to one atomic state (last generated id), so they
+/// jointly produce a single monotonic sequence for their generator id.
+/// Unlike distinct `FIDGenerator`s, clones can be moved to different threads
+/// safely without assigning every thread its own generator id.
+/// `monotonic` mode clamps backward clock steps to the last shared timestamp
+/// and steps ahead of clock on sequence overflow, alike `FIDGenerator`.
+#[derive(Debug, Clone)]
+pub struct SharedGenerator {
+    cfg: FIDGenerator,
+    state: Arc<AtomicU64>,
+}
+
+impl SharedGenerator {
+    /// Create new shared generator
+    ///
+    /// # Failures
+    /// alike `FIDGenerator::new`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// use flowerid::shared::SharedGenerator;
+    /// let gen = SharedGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
+    /// let other = gen.clone();
+    /// assert!(gen.next().unwrap() < other.next().unwrap());
+    /// ```
+    pub fn new(cfg: FIDGeneratorBuilder) -> Result<SharedGenerator> {
+        let gen = FIDGenerator::new(cfg)?;
+        let last = FID::new(gen.timestamp_last, gen.sequence, gen.generator)?;
+        Ok(SharedGenerator {
+            cfg: gen,
+            state: Arc::new(AtomicU64::new(From::from(last))),
+        })
+    }
+
+    /// Generate next id
+    ///
+    /// # Failures
+    /// `Error::SequenceOverflow`
+    /// `Error::SysTimeIsInPast`
+    /// `Error::TimestampOverflow`
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&self) -> Result<FID> {
+        loop {
+            let last = self.state.load(Ordering::Acquire);
+            let last_fid = FID::from(last);
+            let clock = self.cfg.new_timestamp()?;
+            let mut timestamp = self.cfg.clamp_monotonic(clock, last_fid.timestamp());
+            if last_fid.sequence() >= cfg::SEQUENCE_MAX
+                && self.cfg.wait_sequence
+                && self.cfg.can_step_ahead(clock, timestamp)
+            {
+                timestamp += 1;
+            }
+
+            let fid = match timestamp.cmp(&last_fid.timestamp()) {
+                cmp::Ordering::Less if !self.cfg.is_wrapped(timestamp, last_fid.timestamp()) => {
+                    return Err(Error::SysTimeIsInPast)
+                }
+                cmp::Ordering::Less | cmp::Ordering::Greater => {
+                    FID::new(timestamp, self.cfg.first_sequence(), self.cfg.generator)?
+                }
+                cmp::Ordering::Equal => {
+                    let sequence = last_fid.sequence();
+                    if sequence >= cfg::SEQUENCE_MAX {
+                        if self.cfg.wait_sequence && !self.cfg.is_saturated(timestamp) {
+                            self.cfg.wait_next_timestamp()?;
+                            continue;
+                        } else {
+                            return Err(Error::SequenceOverflow(sequence));
+                        }
+                    }
+                    FID::new(timestamp, sequence + 1, self.cfg.generator)?
+                }
+            };
+
+            if self
+                .state
+                .compare_exchange_weak(
+                    last,
+                    From::from(fid.clone()),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .is_ok()
+            {
+                return Ok(self.cfg.seal(fid));
+            }
+        }
+    }
+}
+
+/// Width of `AtomicGenerator` state counter
+const COUNTER_LENGTH: u64 = 64 - cfg::TIMESTAMP_LENGTH;
+const COUNTER_MASK: u64 = (1 << COUNTER_LENGTH) - 1;
+
+/// Shared generator with lock free fast path
+///
+/// State packs (timestamp, sequence counter) into one `AtomicU64`, callers
+/// within one timestamp claim a sequence with single `fetch_add`. Lock is
+/// taken only on timestamp rollover or sequence overflow.
+///
+/// Counter is wider than sequence field, so concurrent increments past
+/// `SEQUENCE_MAX` never carry into the timestamp; such values are discarded.
+/// `monotonic` mode clamps backward clock steps to the timestamp of the state
+/// and steps ahead of clock on sequence overflow, alike `FIDGenerator`.
+#[derive(Debug, Clone)]
+pub struct AtomicGenerator {
+    cfg: FIDGenerator,
+    state: Arc<AtomicU64>,
+    rollover: Arc<Mutex<()>>,
+}
+
+impl AtomicGenerator {
+    /// Create new atomic shared generator
+    ///
+    /// # Failures
+    /// alike `FIDGenerator::new`
+    ///
+    /// # Examples
+    /// ```
+    /// use flowerid::generator::FIDGeneratorBuilder;
+    /// use flowerid::shared::AtomicGenerator;
+    /// let gen = AtomicGenerator::new(FIDGeneratorBuilder::new(0x12c)).unwrap();
+    /// let other = gen.clone();
+    /// assert!(gen.next().unwrap() < other.next().unwrap());
+    /// ```
+    pub fn new(cfg: FIDGeneratorBuilder) -> Result<AtomicGenerator> {
+        let gen = FIDGenerator::new(cfg)?;
+        let state = gen.timestamp_last << COUNTER_LENGTH | u64::from(gen.sequence);
+        Ok(AtomicGenerator {
+            cfg: gen,
+            state: Arc::new(AtomicU64::new(state)),
+            rollover: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Generate next id
+    ///
+    /// # Failures
+    /// `Error::SequenceOverflow`
+    /// `Error::SysTimeIsInPast`
+    /// `Error::TimestampOverflow`
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&self) -> Result<FID> {
+        loop {
+            let last = self.state.load(Ordering::Acquire);
+            let timestamp = self.cfg.new_timestamp()?;
+            let timestamp = self.cfg.clamp_monotonic(timestamp, last >> COUNTER_LENGTH);
+            if last >> COUNTER_LENGTH == timestamp
+                && last & COUNTER_MASK < u64::from(cfg::SEQUENCE_MAX)
+            {
+                let prev = self.state.fetch_add(1, Ordering::AcqRel);
+                if prev >> COUNTER_LENGTH == timestamp
+                    && prev & COUNTER_MASK < u64::from(cfg::SEQUENCE_MAX)
+                {
+                    let sequence = (prev & COUNTER_MASK) as u16 + 1;
+                    return FID::new(timestamp, sequence, self.cfg.generator)
+                        .map(|x| self.cfg.seal(x));
+                }
+            }
+            if let Some(fid) = self.next_rollover()? {
+                return Ok(fid);
+            }
+        }
+    }
+
+    /// Slow path, `None` means retry fast path
+    fn next_rollover(&self) -> Result<Option<FID>> {
+        let _guard = self.rollover.lock().unwrap_or_else(|e| e.into_inner());
+        let last = self.state.load(Ordering::Acquire);
+        let last_timestamp = last >> COUNTER_LENGTH;
+        let clock = self.cfg.new_timestamp()?;
+        let mut timestamp = self.cfg.clamp_monotonic(clock, last_timestamp);
+        if last & COUNTER_MASK >= u64::from(cfg::SEQUENCE_MAX)
+            && self.cfg.wait_sequence
+            && self.cfg.can_step_ahead(clock, timestamp)
+        {
+            timestamp += 1;
+        }
+
+        match timestamp.cmp(&last_timestamp) {
+            cmp::Ordering::Less if !self.cfg.is_wrapped(timestamp, last_timestamp) => {
+                Err(Error::SysTimeIsInPast)
+            }
+            cmp::Ordering::Less | cmp::Ordering::Greater => {
+                let sequence = self.cfg.first_sequence();
+                // late fast path callers see foreign timestamp in `fetch_add`
+                // result and come here, so skipped counters are never issued
+                self.state.store(
+                    timestamp << COUNTER_LENGTH | u64::from(sequence),
+                    Ordering::Release,
+                );
+                FID::new(timestamp, sequence, self.cfg.generator).map(|x| Some(self.cfg.seal(x)))
+            }
+            cmp::Ordering::Equal => {
+                if last & COUNTER_MASK < u64::from(cfg::SEQUENCE_MAX) {
+                    Ok(None)
+                } else if self.cfg.wait_sequence && !self.cfg.is_saturated(timestamp) {
+                    self.cfg.wait_next_timestamp()?;
+                    Ok(None)
+                } else {
+                    Err(Error::SequenceOverflow(cfg::SEQUENCE_MAX))
+                }
+            }
+        }
+    }
+}
+
+/// Generator ids held by alive `UniqueGenerator`s, one flag per id
+static REGISTRY: [AtomicBool; cfg::GENERATOR_MAX as usize + 1] =
+    [const { AtomicBool::new(false) }; cfg::GENERATOR_MAX as usize + 1];
+
+/// Generator holding its generator id in process-wide registry
+///
+/// Only one `UniqueGenerator` per generator id is alive at a time, id is
+/// released on drop. Acquisition and release are lock-free. Read-only
+/// `FIDGenerator` methods are available through `Deref`.
+#[derive(Debug)]
+pub struct UniqueGenerator {
+    gen: FIDGenerator,
+}
+
+/// Create generator reserving its generator id
+///
+/// # Failures
+/// `Error::GeneratorInUse` if another `UniqueGenerator` holds generator id
+/// alike `FIDGenerator::new`
+///
+/// # Examples
+/// ```
+/// use flowerid::Error;
+/// use flowerid::generator::FIDGeneratorBuilder;
+/// use flowerid::shared::unique_generator;
+/// let mut gen = unique_generator(FIDGeneratorBuilder::new(0x12c)).unwrap();
+/// println!("{}", gen.next().unwrap());
+/// let err = unique_generator(FIDGeneratorBuilder::new(0x12c)).unwrap_err();
+/// assert_eq!(err, Error::GeneratorInUse(0x12c));
+/// drop(gen);
+/// assert!(unique_generator(FIDGeneratorBuilder::new(0x12c)).is_ok());
+/// ```
+pub fn unique_generator(cfg: FIDGeneratorBuilder) -> Result<UniqueGenerator> {
+    let gen = FIDGenerator::new(cfg)?;
+    REGISTRY[gen.generator as usize]
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .map_err(|_| Error::GeneratorInUse(gen.generator))?;
+    Ok(UniqueGenerator { gen })
+}
+
+impl UniqueGenerator {
+    /// Generate next id
+    ///
+    /// # Failures
+    /// alike `FIDGenerator::next`
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<FID> {
+        self.gen.next()
+    }
+
+    /// Generate next id with given timestamp
+    ///
+    /// # Failures
+    /// alike `FIDGenerator::next_at`
+    pub fn next_at(&mut self, timestamp: u64) -> Result<FID> {
+        self.gen.next_at(timestamp)
+    }
+}
+
+impl Deref for UniqueGenerator {
+    type Target = FIDGenerator;
+
+    fn deref(&self) -> &FIDGenerator {
+        &self.gen
+    }
+}
+
+impl Drop for UniqueGenerator {
+    fn drop(&mut self) {
+        REGISTRY[self.gen.generator as usize].store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use config::test_constants::*;
+    use std::thread;
+    use stubs::systemtime::SystemTime;
+
+    #[test]
+    fn multithreaded() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let gen =
+            SharedGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let gen = gen.clone();
+                thread::spawn(move || {
+                    (0..500)
+                        .map(|_| u64::from(gen.next().unwrap()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut ids = Vec::new();
+        for x in threads {
+            let chunk = x.join().unwrap();
+            assert!(chunk.windows(2).all(|x| x[0] < x[1]));
+            ids.extend(chunk);
+        }
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 2000);
+        for (i, x) in ids.iter().enumerate() {
+            let fid = FID::from(*x);
+            assert_eq!(fid.timestamp(), TIMESTAMP);
+            assert_eq!(fid.sequence(), i as u16);
+            assert_eq!(fid.generator(), GENERATOR);
+        }
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn overflow() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let gen =
+            SharedGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        let other = gen.clone();
+        for i in 0..(1 << cfg::SEQUENCE_LENGTH) {
+            let gen = if i % 2 == 0 { &gen } else { &other };
+            assert_eq!(gen.next().unwrap().sequence(), i);
+        }
+        assert_eq!(
+            other.next().unwrap_err(),
+            Error::SequenceOverflow((1 << cfg::SEQUENCE_LENGTH) - 1)
+        );
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn unique_generator() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let mut gen = super::unique_generator(FIDGeneratorBuilder::new(GENERATOR)).unwrap();
+        assert_eq!(gen.next().unwrap().generator(), GENERATOR);
+        assert_eq!(gen.generator_id(), GENERATOR);
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| super::unique_generator(FIDGeneratorBuilder::new(GENERATOR)))
+            })
+            .collect();
+        for x in threads {
+            assert_eq!(x.join().unwrap().unwrap_err(), Error::GeneratorInUse(GENERATOR));
+        }
+        let other = super::unique_generator(FIDGeneratorBuilder::new(GENERATOR + 1)).unwrap();
+        drop(gen);
+        let gen = super::unique_generator(FIDGeneratorBuilder::new(GENERATOR)).unwrap();
+        assert_eq!(
+            super::unique_generator(FIDGeneratorBuilder::new(GENERATOR + 1)).unwrap_err(),
+            Error::GeneratorInUse(GENERATOR + 1)
+        );
+        assert_eq!(
+            super::unique_generator(FIDGeneratorBuilder::new(cfg::GENERATOR_MAX + 1)).unwrap_err(),
+            Error::GeneratorOverflow(cfg::GENERATOR_MAX + 1)
+        );
+        drop((gen, other));
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn atomic_multithreaded() {
+        let lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let gen =
+            AtomicGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let gen = gen.clone();
+                thread::spawn(move || {
+                    (0..500)
+                        .map(|_| u64::from(gen.next().unwrap()))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut ids = Vec::new();
+        for x in threads {
+            let chunk = x.join().unwrap();
+            assert!(chunk.windows(2).all(|x| x[0] < x[1]));
+            ids.extend(chunk);
+        }
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 2000);
+        for x in ids.iter() {
+            let fid = FID::from(*x);
+            assert_eq!(fid.timestamp(), TIMESTAMP);
+            assert_eq!(fid.generator(), GENERATOR);
+        }
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn atomic_overflow() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let gen =
+            AtomicGenerator::new(FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false)).unwrap();
+        let other = gen.clone();
+        for i in 0..(1 << cfg::SEQUENCE_LENGTH) {
+            let gen = if i % 2 == 0 { &gen } else { &other };
+            assert_eq!(gen.next().unwrap().sequence(), i);
+        }
+        assert_eq!(
+            other.next().unwrap_err(),
+            Error::SequenceOverflow(cfg::SEQUENCE_MAX)
+        );
+        lock_sys_time.add(1);
+        assert_eq!(gen.next().unwrap().components(), (TIMESTAMP + 1, 0, GENERATOR));
+        assert_eq!(other.next().unwrap().components(), (TIMESTAMP + 1, 1, GENERATOR));
+        lock_sys_time.add(-2);
+        assert_eq!(gen.next().unwrap_err(), Error::SysTimeIsInPast);
+        SystemTime::unlock(lock_sys_time);
+    }
+
+    #[test]
+    fn monotonic() {
+        let mut lock_sys_time = SystemTime::lock(
+            cfg::gbuilder_defaults::TIMESTAMP_OFFSET.abs() * 1000 + TIMESTAMP as i64,
+        );
+        let builder = || FIDGeneratorBuilder::new(GENERATOR).wait_sequence(false).monotonic(true);
+        let shared = SharedGenerator::new(builder()).unwrap();
+        let atomic = AtomicGenerator::new(builder()).unwrap();
+        let (shared_other, atomic_other) = (shared.clone(), atomic.clone());
+        assert_eq!(shared.next().unwrap().components(), (TIMESTAMP, 0, GENERATOR));
+        assert_eq!(atomic.next().unwrap().components(), (TIMESTAMP, 0, GENERATOR));
+        lock_sys_time.add(-5);
+        assert_eq!(shared_other.next().unwrap().components(), (TIMESTAMP, 1, GENERATOR));
+        assert_eq!(atomic_other.next().unwrap().components(), (TIMESTAMP, 1, GENERATOR));
+        assert_eq!(shared.next().unwrap().components(), (TIMESTAMP, 2, GENERATOR));
+        assert_eq!(atomic.next().unwrap().components(), (TIMESTAMP, 2, GENERATOR));
+        lock_sys_time.add(6);
+        assert_eq!(shared.next().unwrap().components(), (TIMESTAMP + 1, 0, GENERATOR));
+        assert_eq!(atomic.next().unwrap().components(), (TIMESTAMP + 1, 0, GENERATOR));
+
+        let builder = || FIDGeneratorBuilder::new(GENERATOR).monotonic(true);
+        let shared = SharedGenerator::new(builder()).unwrap();
+        let atomic = AtomicGenerator::new(builder()).unwrap();
+        shared.next().unwrap();
+        atomic.next().unwrap();
+        lock_sys_time.add(-5);
+        for _ in 0..cfg::SEQUENCE_MAX {
+            shared.next().unwrap();
+            atomic.next().unwrap();
+        }
+        // steps ahead of clock instead of waiting until it catches up
+        assert_eq!(shared.next().unwrap().components(), (TIMESTAMP + 2, 0, GENERATOR));
+        assert_eq!(atomic.next().unwrap().components(), (TIMESTAMP + 2, 0, GENERATOR));
+        SystemTime::unlock(lock_sys_time);
+    }
+}
@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::ops;
 use std::time;
 use std::sync::atomic;
@@ -10,16 +11,14 @@ pub struct SystemTime(i64);
 #[derive(Debug)]
 pub struct SystemTimeLock;
 
-#[derive(Debug, Clone, Copy)]
-struct SystemTimeInstance {
-    start_value: i64,
-    locked: bool,
-}
+static START_VALUE: atomic::AtomicI64 = atomic::AtomicI64::new(0);
+static LOCKED: atomic::AtomicBool = atomic::AtomicBool::new(false);
+static FLAG: atomic::AtomicBool = atomic::AtomicBool::new(false);
 
 impl SystemTime {
     pub fn now() -> SystemTime {
-        assert!(SystemTime::instance().locked);
-        SystemTime(SystemTime::instance().start_value)
+        assert!(LOCKED.load(atomic::Ordering::SeqCst));
+        SystemTime(START_VALUE.load(atomic::Ordering::SeqCst))
     }
 
     pub fn duration_since(&self, earlier: SystemTime) -> Result<time::Duration, ()> {
@@ -30,29 +29,17 @@ impl SystemTime {
         }
     }
 
-    fn instance() -> &'static mut SystemTimeInstance {
-        static mut INST: SystemTimeInstance = SystemTimeInstance {
-            start_value: 0,
-            locked: false,
-        };
-        unsafe { &mut INST }
-    }
-
-    fn flag_instance() -> &'static mut atomic::AtomicBool {
-        static mut INST: atomic::AtomicBool = atomic::ATOMIC_BOOL_INIT;
-        unsafe { &mut INST }
+    pub fn checked_add(&self, other: time::Duration) -> Option<SystemTime> {
+        i64::try_from(other.as_millis())
+            .ok()
+            .and_then(|x| self.0.checked_add(x))
+            .map(SystemTime)
     }
 
     pub fn lock(start_value: i64) -> SystemTimeLock {
-        loop {
-            if SystemTime::flag_instance().swap(true, atomic::Ordering::Acquire) == false {
-                break;
-            }
-        }
-        *SystemTime::instance() = SystemTimeInstance {
-            start_value,
-            locked: true,
-        };
+        while FLAG.swap(true, atomic::Ordering::Acquire) {}
+        START_VALUE.store(start_value, atomic::Ordering::SeqCst);
+        LOCKED.store(true, atomic::Ordering::SeqCst);
         SystemTimeLock
     }
 
@@ -63,26 +50,26 @@ impl SystemTime {
 
 impl SystemTimeLock {
     pub fn add(&mut self, value: i64) {
-        SystemTime::instance().start_value += value;
+        START_VALUE.fetch_add(value, atomic::Ordering::SeqCst);
     }
 }
 
 impl Drop for SystemTimeLock {
     fn drop(&mut self) {
-        SystemTime::instance().locked = false;
-        SystemTime::flag_instance().store(false, atomic::Ordering::Release);
+        LOCKED.store(false, atomic::Ordering::SeqCst);
+        FLAG.store(false, atomic::Ordering::Release);
     }
 }
 
 impl ops::AddAssign<time::Duration> for SystemTime {
     fn add_assign(&mut self, other: time::Duration) {
-        self.0 += (other.as_secs() * 1000 + ((other.subsec_nanos() / 1000 / 1000) as u64)) as i64;
+        self.0 += (other.as_secs() * 1000 + (other.subsec_millis() as u64)) as i64;
     }
 }
 
 impl ops::SubAssign<time::Duration> for SystemTime {
     fn sub_assign(&mut self, other: time::Duration) {
-        self.0 -= (other.as_secs() * 1000 + ((other.subsec_nanos() / 1000 / 1000) as u64)) as i64;
+        self.0 -= (other.as_secs() * 1000 + (other.subsec_millis() as u64)) as i64;
     }
 }
 